@@ -0,0 +1,257 @@
+use std::error::Error;
+
+use serde::Serialize;
+
+use crate::{
+    config::AlternativesConfig,
+    constraints::constraint_store::ConstraintStore,
+    report::report,
+    schedule::{Schedule, Slot},
+    scoring::Scoring,
+};
+
+/// The best schedules found by a search that differ from each other by at least a minimum
+/// distance
+///
+/// The distance between two schedules is the number of constraints placed differently (see
+/// `Schedule::placement_distance`). When a schedule is offered that is closer than the minimum
+/// distance to schedules in the archive, only the better of them is kept
+#[derive(Default)]
+pub struct AlternativeArchive {
+    count: usize,
+    min_distance: u32,
+    entries: Vec<(Schedule, u32)>,
+}
+
+impl AlternativeArchive {
+    /// Creates an empty archive
+    ///
+    /// # Arguments
+    /// * `alternatives_config` - The number of schedules to keep and the minimum distance between
+    ///   them
+    pub fn new(alternatives_config: &AlternativesConfig) -> Self {
+        AlternativeArchive {
+            count: alternatives_config.count as usize,
+            min_distance: alternatives_config.min_distance.max(1),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Offers a schedule to the archive
+    ///
+    /// The schedule is rejected if a schedule closer than the minimum distance has a lower
+    /// penalty. Otherwise it replaces the schedules closer than the minimum distance, so on ties
+    /// the most recently offered schedule is kept
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule to offer
+    /// * `total_penalty` - The penalty of the schedule
+    ///
+    /// # Returns
+    /// * `bool` - Whether the schedule was added to the archive
+    pub fn offer(&mut self, schedule: &Schedule, total_penalty: u32) -> bool {
+        // NOTE: Only the best schedule is needed when no alternatives are requested, and it is
+        // tracked by the searches themselves
+        if self.count <= 1 {
+            return false;
+        }
+
+        // NOTE: Twice the requested number of schedules are kept, as the schedules close to the
+        // best schedule are dropped when it is offered
+        let capacity = self.count * 2;
+        if self.entries.len() == capacity
+            && self
+                .entries
+                .last()
+                .is_some_and(|(_, worst_total_penalty)| total_penalty > *worst_total_penalty)
+        {
+            return false;
+        }
+
+        let distances: Vec<u32> = self
+            .entries
+            .iter()
+            .map(|(entry, _)| entry.placement_distance(schedule))
+            .collect();
+        if self
+            .entries
+            .iter()
+            .zip(&distances)
+            .any(|((_, entry_total_penalty), distance)| {
+                *distance < self.min_distance && *entry_total_penalty < total_penalty
+            })
+        {
+            return false;
+        }
+
+        let mut distances = distances.into_iter();
+        self.entries.retain(|_| {
+            distances
+                .next()
+                .is_some_and(|distance| distance >= self.min_distance)
+        });
+
+        let position = self
+            .entries
+            .partition_point(|(_, entry_total_penalty)| *entry_total_penalty <= total_penalty);
+        self.entries
+            .insert(position, (schedule.clone(), total_penalty));
+        self.entries.truncate(capacity);
+        true
+    }
+
+    /// Offers every schedule of another archive to this archive
+    ///
+    /// # Arguments
+    /// * `other` - The archive to take the schedules from
+    pub fn merge(&mut self, other: &AlternativeArchive) {
+        for (schedule, total_penalty) in &other.entries {
+            self.offer(schedule, *total_penalty);
+        }
+    }
+
+    /// Lists the schedules of the archive other than the best schedule, ordered by penalty
+    ///
+    /// # Arguments
+    /// * `best_schedule` - The best schedule found by the search
+    /// * `constraints` - The constraint store containing all constraints
+    ///
+    /// # Returns
+    /// * `Vec<Alternative>` - The alternatives with how each differs from the best schedule
+    pub fn alternatives(
+        &self,
+        best_schedule: &Schedule,
+        constraints: &ConstraintStore,
+    ) -> Vec<Alternative> {
+        self.entries
+            .iter()
+            .filter(|(schedule, _)| schedule.placement_distance(best_schedule) >= self.min_distance)
+            .take(self.count.saturating_sub(1))
+            .map(|(schedule, total_penalty)| Alternative {
+                schedule: schedule.clone(),
+                total_penalty: *total_penalty,
+                distance: schedule.placement_distance(best_schedule),
+                differences: constraints
+                    .into_iter()
+                    .filter_map(|constraint| {
+                        let best_slot =
+                            best_schedule.get_scheduled_slot_for_constraint(constraint.id);
+                        let slot = schedule.get_scheduled_slot_for_constraint(constraint.id);
+                        (best_slot != slot).then(|| PlacementDifference {
+                            constraint_id: constraint.id,
+                            name: constraint.name.clone(),
+                            best_slot: best_slot.cloned(),
+                            alternative_slot: slot.cloned(),
+                        })
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// A constraint placed differently in an alternative than in the best schedule
+///
+/// * `constraint_id` - The id of the constraint
+/// * `name` - The name of the constraint
+/// * `best_slot` - The slot of the constraint in the best schedule, if scheduled
+/// * `alternative_slot` - The slot of the constraint in the alternative, if scheduled
+#[derive(Clone, Debug, Serialize)]
+pub struct PlacementDifference {
+    pub constraint_id: u32,
+    pub name: String,
+    pub best_slot: Option<Slot>,
+    pub alternative_slot: Option<Slot>,
+}
+
+/// A good schedule that differs from the best schedule by at least the minimum distance
+///
+/// * `schedule` - The alternative schedule
+/// * `total_penalty` - The penalty of the alternative
+/// * `distance` - The number of constraints placed differently than in the best schedule
+/// * `differences` - The constraints placed differently than in the best schedule
+#[derive(Clone, Debug, Serialize)]
+pub struct Alternative {
+    #[serde(skip_serializing)]
+    pub schedule: Schedule,
+    pub total_penalty: u32,
+    pub distance: u32,
+    pub differences: Vec<PlacementDifference>,
+}
+
+/// Writes how each of the alternatives differs from the best schedule to the report output
+///
+/// # Arguments
+/// * `alternatives` - The alternatives to report
+/// * `best_total_penalty` - The penalty of the best schedule
+/// * `scoring` - The scoring the penalties were calculated with
+pub fn print_alternatives_report(
+    alternatives: &[Alternative],
+    best_total_penalty: u32,
+    scoring: &Scoring,
+) {
+    let describe_slot = |slot: &Option<Slot>| match slot {
+        Some(slot) => slot.to_string(),
+        None => "unscheduled".to_string(),
+    };
+
+    report!("=== ALTERNATIVES REPORT ===");
+    if alternatives.is_empty() {
+        report!("No alternative schedules far enough from the best schedule were found");
+    }
+
+    for (i, alternative) in alternatives.iter().enumerate() {
+        report!(
+            "Alternative {}: penalty {} ({} compared to the best) | {} constraints placed \
+             differently",
+            i + 1,
+            scoring.format_score(alternative.total_penalty),
+            scoring.format_difference(alternative.total_penalty, best_total_penalty),
+            alternative.distance
+        );
+        for difference in &alternative.differences {
+            report!(
+                "  └─ [{}:{}] {} -> {}",
+                difference.constraint_id,
+                difference.name.to_uppercase(),
+                describe_slot(&difference.best_slot),
+                describe_slot(&difference.alternative_slot)
+            );
+        }
+    }
+
+    report!("===========================");
+}
+
+/// Exports each alternative to its own csv file next to the best schedule, along with a json
+/// summary of how the alternatives differ from the best schedule
+///
+/// The alternatives are exported to `<output>_alternative_<n>.csv` and the summary to
+/// `<output>_alternatives.json`, where `<output>` is the output path without its extension
+///
+/// # Arguments
+/// * `alternatives` - The alternatives to export
+/// * `output_path` - The path the best schedule is exported to
+/// * `constraints` - The constraint store containing all constraints
+pub fn export_alternatives(
+    alternatives: &[Alternative],
+    output_path: &str,
+    constraints: &ConstraintStore,
+) -> Result<(), Box<dyn Error>> {
+    let output_stem = output_path
+        .strip_suffix(".csv")
+        .unwrap_or(output_path)
+        .to_string();
+
+    for (i, alternative) in alternatives.iter().enumerate() {
+        alternative.schedule.export_to_csv(
+            format!("{}_alternative_{}.csv", output_stem, i + 1),
+            constraints,
+        )?;
+    }
+
+    let json_string = serde_json::to_string_pretty(alternatives)?;
+    std::fs::write(format!("{}_alternatives.json", output_stem), json_string)?;
+
+    Ok(())
+}
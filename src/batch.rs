@@ -0,0 +1,261 @@
+use std::error::Error;
+
+use csv::Writer;
+use log::{info, warn};
+
+use crate::{
+    cli::Outcome,
+    config::{BASELINE_SCENARIO, Config, FilterConfig, scenario_file_name},
+    constraints::{
+        constraint_store::ConstraintStore, penalties::calculate_detailed_penalties,
+        penalty::Penalty,
+    },
+    global_search::run_global_search,
+    report::report,
+    scoring::Scoring,
+    stop_condition::is_interrupted,
+};
+
+/// The penalty types compared between scenarios, in the order of the comparison table
+const COMPARED_PENALTIES: [Penalty; 4] = [
+    Penalty::Presence,
+    Penalty::AllowedSlots,
+    Penalty::PreferredSlots,
+    Penalty::Gap,
+];
+
+/// The schedule of a scenario of the batch mode and the penalties it incurs
+///
+/// * `name` - The name of the scenario
+/// * `schedule_path` - The csv file the schedule of the scenario is exported to
+/// * `constraint_count` - The number of constraints kept by the filter of the scenario
+/// * `scheduled_count` - The number of those constraints that are scheduled
+/// * `penalties` - The total penalty incurred for each compared penalty type
+/// * `total_penalty` - The total penalty incurred, with the configured penalties
+/// * `outcome` - How well the schedule satisfies the constraints of the scenario
+#[derive(Clone, Debug)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub schedule_path: String,
+    pub constraint_count: usize,
+    pub scheduled_count: usize,
+    pub penalties: Vec<(Penalty, u32)>,
+    pub total_penalty: u32,
+    pub outcome: Outcome,
+}
+
+impl ScenarioResult {
+    /// Returns the percentage of the constraints of the scenario that are scheduled
+    pub fn fulfilment(&self) -> f32 {
+        self.scheduled_count as f32 / self.constraint_count as f32 * 100.0
+    }
+}
+
+/// Solves the baseline scenario keeping all constraints, followed by each configured scenario
+///
+/// Every scenario is solved with the configured search, and its schedule is exported to
+/// `<output>_<scenario>.csv`, where `<output>` is the output path without its extension
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing all constraints
+/// * `config` - The configuration of the solves and the scenarios
+///
+/// # Returns
+/// * `Vec<ScenarioResult>` - The result of each solved scenario, starting with the baseline
+pub fn run_batch(
+    constraints: &ConstraintStore,
+    config: &Config,
+) -> Result<Vec<ScenarioResult>, Box<dyn Error>> {
+    let baseline = FilterConfig::default();
+    let scenarios = [(BASELINE_SCENARIO, &baseline)].into_iter().chain(
+        config
+            .batch_config
+            .scenarios
+            .iter()
+            .map(|scenario| (scenario.name.as_str(), &scenario.filter)),
+    );
+
+    let mut results: Vec<ScenarioResult> = Vec::new();
+    for (name, filter_config) in scenarios {
+        if is_interrupted() {
+            warn!("Batch interrupted, the remaining scenarios are not solved");
+            break;
+        }
+
+        let filter = filter_config
+            .filter()
+            .expect("LOGIC ERROR: The filters of the scenarios are validated with the config");
+        let scenario_constraints = constraints.filter(&filter);
+        if scenario_constraints.is_empty() {
+            warn!("Scenario {:?} keeps no constraints and is not solved", name);
+            continue;
+        }
+
+        info!(
+            "Solving scenario {:?} with {} constraints",
+            name,
+            scenario_constraints.len()
+        );
+        results.push(solve_scenario(name, &scenario_constraints, config)?);
+    }
+
+    Ok(results)
+}
+
+/// Solves a scenario and exports its schedule
+///
+/// # Arguments
+/// * `name` - The name of the scenario
+/// * `constraints` - The constraints kept by the filter of the scenario
+/// * `config` - The configuration of the solve
+fn solve_scenario(
+    name: &str,
+    constraints: &ConstraintStore,
+    config: &Config,
+) -> Result<ScenarioResult, Box<dyn Error>> {
+    let scoring = Scoring::new(
+        &config.scoring_config,
+        constraints,
+        &config.penalties_config,
+    )?;
+    let search_config = scoring.search_config(config)?;
+
+    let (schedule, _, _) = run_global_search(constraints, &search_config)?;
+
+    let output_stem = config
+        .output_path
+        .strip_suffix(".csv")
+        .unwrap_or(&config.output_path);
+    let schedule_path = format!("{}_{}.csv", output_stem, scenario_file_name(name));
+    schedule.export_to_csv(schedule_path.clone(), constraints)?;
+
+    let detailed_penalties =
+        calculate_detailed_penalties(constraints, &schedule, &config.penalties_config);
+    let penalties: Vec<(Penalty, u32)> = COMPARED_PENALTIES
+        .iter()
+        .map(|compared| {
+            let total = detailed_penalties
+                .values()
+                .flatten()
+                .filter(|(penalty, _)| penalty == compared)
+                .map(|(_, value)| value)
+                .sum();
+            (*compared, total)
+        })
+        .collect();
+
+    Ok(ScenarioResult {
+        name: name.to_string(),
+        schedule_path,
+        constraint_count: constraints.len(),
+        scheduled_count: constraints
+            .into_iter()
+            .filter(|constraint| {
+                schedule
+                    .get_scheduled_slot_for_constraint(constraint.id)
+                    .is_some()
+            })
+            .count(),
+        total_penalty: penalties.iter().map(|(_, value)| value).sum(),
+        penalties,
+        outcome: Outcome::of_schedule(constraints, &schedule, &config.penalties_config),
+    })
+}
+
+/// Writes a table comparing the penalties and fulfilment of the scenarios to the report output
+///
+/// # Arguments
+/// * `results` - The results of the scenarios, starting with the baseline
+pub fn print_comparison_table(results: &[ScenarioResult]) {
+    let name_width = results
+        .iter()
+        .map(|result| result.name.len())
+        .chain(["SCENARIO".len()])
+        .max()
+        .unwrap_or_default();
+    let baseline_penalty = results
+        .iter()
+        .find(|result| result.name == BASELINE_SCENARIO)
+        .map(|result| result.total_penalty);
+
+    let mut header = format!(
+        "{:<name_width$}  {:>11}  {:>9}  {:>10}",
+        "SCENARIO", "CONSTRAINTS", "SCHEDULED", "FULFILMENT"
+    );
+    for penalty in COMPARED_PENALTIES {
+        header += &format!("  {:>14}", penalty.to_string().to_uppercase());
+    }
+    header += &format!("  {:>7}  {:>11}  {}", "TOTAL", "VS BASELINE", "OUTCOME");
+    report!("{}", header);
+
+    for result in results {
+        let mut row = format!(
+            "{:<name_width$}  {:>11}  {:>9}  {:>9.1}%",
+            result.name,
+            result.constraint_count,
+            result.scheduled_count,
+            result.fulfilment()
+        );
+        for (_, value) in &result.penalties {
+            row += &format!("  {:>14}", value);
+        }
+        let versus_baseline = match baseline_penalty {
+            Some(baseline_penalty) => {
+                format!(
+                    "{:+}",
+                    result.total_penalty as i64 - baseline_penalty as i64
+                )
+            }
+            None => "-".to_string(),
+        };
+        row += &format!(
+            "  {:>7}  {:>11}  {}",
+            result.total_penalty, versus_baseline, result.outcome
+        );
+        report!("{}", row);
+    }
+}
+
+/// Exports the comparison of the scenarios to a csv file
+///
+/// # Arguments
+/// * `results` - The results of the scenarios, starting with the baseline
+/// * `output_path` - The path of the csv file
+pub fn export_comparison(
+    results: &[ScenarioResult],
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    info!("Exporting scenario comparison to csv ({:?})", output_path);
+    let mut csv_writer = Writer::from_path(output_path)?;
+
+    let mut header: Vec<String> = [
+        "Scenario",
+        "Schedule",
+        "Constraints",
+        "Scheduled",
+        "Fulfilment",
+    ]
+    .iter()
+    .map(|column| column.to_string())
+    .collect();
+    header.extend(COMPARED_PENALTIES.iter().map(|penalty| penalty.to_string()));
+    header.extend(["Total".to_string(), "Outcome".to_string()]);
+    csv_writer.write_record(&header)?;
+
+    for result in results {
+        let mut record = vec![
+            result.name.clone(),
+            result.schedule_path.clone(),
+            result.constraint_count.to_string(),
+            result.scheduled_count.to_string(),
+            format!("{:.1}", result.fulfilment()),
+        ];
+        record.extend(result.penalties.iter().map(|(_, value)| value.to_string()));
+        record.extend([result.total_penalty.to_string(), result.outcome.to_string()]);
+        csv_writer.write_record(&record)?;
+    }
+
+    csv_writer.flush()?;
+    info!("Exported scenario comparison to csv");
+    Ok(())
+}
@@ -0,0 +1,212 @@
+pub mod commands;
+
+use std::{fmt, path::PathBuf, process::ExitCode};
+
+use clap::{Parser, Subcommand};
+
+use crate::{
+    config::PenaltiesConfig,
+    constraints::{
+        constraint_store::ConstraintStore, penalties::calculate_detailed_penalties,
+        penalty::Penalty,
+    },
+    schedule::Schedule,
+    schema::SchemaKind,
+};
+
+/// Schedules weekly tasks by minimising the penalties of their constraints
+///
+/// The exit code tells how well the constraints are satisfied (see `Outcome`)
+#[derive(Parser, Debug)]
+#[command(name = "scheduler", version)]
+pub struct Cli {
+    /// The configuration file. Defaults to config.toml, or the default values if it does not exist
+    #[arg(short, long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// The profile of the configuration file to apply, e.g. `quick` for its `[quick]` table
+    #[arg(short, long, global = true, env = "SCHEDULER_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Overrides a configuration value, e.g. `--set iterations=5000` or
+    /// `--set annealing_config.cooling_schedule=Linear`. Can be repeated
+    #[arg(short = 's', long = "set", global = true, value_name = "KEY=VALUE")]
+    pub overrides: Vec<String>,
+
+    /// The constraint file, overriding `constraint_file_path` of the configuration
+    #[arg(long, global = true, value_name = "FILE")]
+    pub constraints: Option<String>,
+
+    /// A constraint file merged into the constraints, overriding `constraint_include_paths` of the
+    /// configuration. Can be repeated
+    #[arg(long = "include", global = true, value_name = "FILE")]
+    pub includes: Vec<String>,
+
+    /// The file to write the reports to instead of standard out, overriding
+    /// `report_config.output_path` of the configuration
+    #[arg(long, global = true, value_name = "FILE")]
+    pub report: Option<String>,
+
+    /// Solves only the constraints matching a selector (`tag:<tag>`, `type:<name>` or `id:<id>`),
+    /// overriding `filter_config.include` of the configuration. Can be repeated
+    #[arg(long = "only", global = true, value_name = "SELECTOR")]
+    pub only: Vec<String>,
+
+    /// Leaves out the constraints matching a selector, overriding `filter_config.exclude` of the
+    /// configuration. Can be repeated
+    #[arg(long = "exclude", global = true, value_name = "SELECTOR")]
+    pub exclude: Vec<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Collects the configuration overrides of the command line, so that the files passed as
+    /// arguments are validated like any other configuration value
+    ///
+    /// # Returns
+    /// * `Vec<String>` - The `--set` overrides, followed by the overrides of `--constraints`,
+    ///   `--include`, `--report`, `--only`, `--exclude` and `solve --output`
+    pub fn config_overrides(&self) -> Vec<String> {
+        let mut overrides = self.overrides.clone();
+        if let Some(constraint_file_path) = &self.constraints {
+            overrides.push(format!("constraint_file_path={:?}", constraint_file_path));
+        }
+        if !self.includes.is_empty() {
+            overrides.push(format!("constraint_include_paths={:?}", self.includes));
+        }
+        if let Some(report_path) = &self.report {
+            overrides.push(format!("report_config.output_path={:?}", report_path));
+        }
+        if !self.only.is_empty() {
+            overrides.push(format!("filter_config.include={:?}", self.only));
+        }
+        if !self.exclude.is_empty() {
+            overrides.push(format!("filter_config.exclude={:?}", self.exclude));
+        }
+        if let Some(Command::Solve {
+            output: Some(output_path),
+        }) = &self.command
+        {
+            overrides.push(format!("output_path={:?}", output_path));
+        }
+        overrides
+    }
+}
+
+/// The commands of the scheduler. Solves when no command is given
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Optimises a schedule for the constraints and exports it
+    Solve {
+        /// The csv file to export the schedule to, overriding `output_path` of the configuration
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<String>,
+    },
+    /// Checks that the constraint file can be loaded and analyses whether its constraints can all
+    /// be satisfied
+    Validate,
+    /// Scores an existing schedule against the constraints
+    Check {
+        /// The csv file of the schedule
+        schedule: String,
+    },
+    /// Writes an existing schedule as a coloured weekly grid to the report output
+    Render {
+        /// The csv file of the schedule
+        schedule: String,
+    },
+    /// Edits a schedule in an interactive week view, moving and pinning constraints and
+    /// re-optimising the unpinned ones
+    Edit {
+        /// The csv file of the schedule to edit. A new schedule is optimised when left out
+        schedule: Option<String>,
+    },
+    /// Compares two schedules of the same constraints
+    Diff {
+        /// The csv file of the first schedule
+        first: String,
+        /// The csv file of the second schedule
+        second: String,
+    },
+    /// Races parameter configurations against each other and writes the best one to
+    /// `tuning_config.output_path`
+    Tune,
+    /// Solves the scenarios of `batch_config` and prints a table comparing their penalties and
+    /// fulfilment
+    Batch,
+    /// Prints the JSON Schema of a file, for autocompletion and validation in editors
+    Schema {
+        /// The file or type to print the schema of
+        #[arg(value_enum)]
+        kind: SchemaKind,
+        /// The file to write the schema to, instead of standard out
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+/// How well the constraints are satisfied, reported through the exit code of the command
+///
+/// * `Perfect` - No penalty is incurred (exit code 0)
+/// * `Degraded` - Only preferences are violated, i.e. preferred slots and gaps (exit code 2)
+/// * `Infeasible` - A constraint is not scheduled, or not scheduled in one of its allowed slots
+///   (exit code 3)
+///
+/// NOTE: Exit code 1 is left for errors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Perfect,
+    Degraded,
+    Infeasible,
+}
+
+impl Outcome {
+    /// Classifies how well a schedule satisfies the constraints
+    ///
+    /// # Arguments
+    /// * `constraints` - The constraint store containing all constraints
+    /// * `schedule` - The schedule to classify
+    /// * `penalties_config` - The configuration specifying values used for penalties
+    pub fn of_schedule(
+        constraints: &ConstraintStore,
+        schedule: &Schedule,
+        penalties_config: &PenaltiesConfig,
+    ) -> Outcome {
+        let detailed_penalties =
+            calculate_detailed_penalties(constraints, schedule, penalties_config);
+        let incurred_penalties = detailed_penalties
+            .values()
+            .flatten()
+            .filter(|(_, value)| *value > 0);
+
+        let mut outcome = Outcome::Perfect;
+        for (penalty, _) in incurred_penalties {
+            match penalty {
+                Penalty::Presence | Penalty::AllowedSlots => return Outcome::Infeasible,
+                Penalty::PreferredSlots | Penalty::Gap => outcome = Outcome::Degraded,
+            }
+        }
+        outcome
+    }
+
+    /// Returns the exit code reporting the outcome
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Outcome::Perfect => ExitCode::SUCCESS,
+            Outcome::Degraded => ExitCode::from(2),
+            Outcome::Infeasible => ExitCode::from(3),
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Perfect => write!(f, "Perfect"),
+            Outcome::Degraded => write!(f, "Degraded"),
+            Outcome::Infeasible => write!(f, "Infeasible"),
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::{fs, path::Path};
+
+use anyhow::{Result, anyhow};
+
+use crate::{
+    alternatives::{export_alternatives, print_alternatives_report},
+    batch::{export_comparison, print_comparison_table, run_batch},
+    cli::Outcome,
+    config::{Config, SearchMode},
+    constraints::{
+        constraint_store::ConstraintStore,
+        penalties::{calculate_penalties, print_penalty_report},
+    },
+    explanation::{explain_schedule, export_explanations, print_explanation_report},
+    global_search::run_global_search,
+    multi_objective::export_pareto_front,
+    presolve::{PresolveError, presolve},
+    report::{is_report_output_terminal, report},
+    schedule::{Schedule, Slot, grid_renderer::print_grid_report},
+    schema::{SchemaKind, generate_schema},
+    scoring::Scoring,
+    tui::{editor::Editor, run_editor},
+    tuning::run_tuning,
+};
+
+/// Optimises a schedule for the constraints and exports it, along with the alternatives and the
+/// Pareto front when requested
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints to schedule
+/// * `config` - The configuration of the search
+///
+/// # Returns
+/// * `Outcome` - How well the best schedule satisfies the constraints
+/// * `PresolveError` - If strict presolve finds the constraints infeasible, before searching
+pub fn solve(constraints: &ConstraintStore, config: &Config) -> Result<Outcome> {
+    if config.presolve_config.enabled {
+        let presolve_report = presolve(constraints, &config.penalties_config);
+        presolve_report.print();
+        if config.presolve_config.strict && presolve_report.is_infeasible() {
+            let error = PresolveError::Infeasible(presolve_report.issues.len());
+            report!("Solve aborted, no schedule is exported: {}", error);
+            return Err(error.into());
+        }
+    }
+
+    // NOTE: The searches compare total penalties, so lexicographic scoring is applied by searching
+    // with penalties scaled per tier. The reports use the configured penalties
+    let scoring = Scoring::new(
+        &config.scoring_config,
+        constraints,
+        &config.penalties_config,
+    )?;
+    let search_config = scoring.search_config(config)?;
+
+    let (schedule, total_score, stats) = run_global_search(constraints, &search_config)?;
+    let (_, total_incurred_penalty) =
+        calculate_penalties(constraints, &schedule, &config.penalties_config);
+
+    schedule
+        .export_to_csv(config.output_path.clone(), constraints)
+        .map_err(|error| anyhow!("Could not export to csv: {}", error))?;
+
+    if config.search_mode == SearchMode::MultiObjective {
+        export_pareto_front(&stats.pareto_front, &config.output_path, constraints)
+            .map_err(|error| anyhow!("Could not export the Pareto front: {}", error))?;
+    }
+
+    let alternatives = stats
+        .alternative_archive
+        .alternatives(&schedule, constraints);
+    if config.alternatives_config.count > 1 {
+        export_alternatives(&alternatives, &config.output_path, constraints)
+            .map_err(|error| anyhow!("Could not export alternatives: {}", error))?;
+    }
+
+    stats.generate_optimisation_report();
+    constraints.print_schedule_report(&schedule, total_incurred_penalty);
+    print_penalty_report(
+        constraints,
+        &schedule,
+        total_incurred_penalty,
+        &config.penalties_config,
+    );
+    scoring.print_score_report(total_score);
+    if config.report_config.grid {
+        print_grid_report(
+            &schedule,
+            constraints,
+            &config.penalties_config,
+            config.report_config.use_colour(is_report_output_terminal()),
+        );
+    }
+
+    if config.alternatives_config.count > 1 {
+        print_alternatives_report(&alternatives, total_score, &scoring);
+    }
+
+    explain(constraints, config, &schedule)?;
+    Ok(Outcome::of_schedule(
+        constraints,
+        &schedule,
+        &config.penalties_config,
+    ))
+}
+
+/// Analyses whether the constraints can all be satisfied, without optimising
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints to analyse
+/// * `config` - The configuration specifying the values used for penalties
+///
+/// # Returns
+/// * `Outcome` - `Infeasible` if the constraints cannot all be satisfied, `Degraded` if a penalty
+///   cannot be avoided and `Perfect` otherwise
+pub fn validate(constraints: &ConstraintStore, config: &Config) -> Outcome {
+    let presolve_report = presolve(constraints, &config.penalties_config);
+    presolve_report.print();
+
+    let outcome = if presolve_report.is_infeasible() {
+        Outcome::Infeasible
+    } else if presolve_report.lower_bound > 0 {
+        Outcome::Degraded
+    } else {
+        Outcome::Perfect
+    };
+
+    report!(
+        "{} constraints loaded | {} issues | lower bound on the total penalty: {} | {}",
+        constraints.len(),
+        presolve_report.issues.len(),
+        presolve_report.lower_bound,
+        outcome
+    );
+    outcome
+}
+
+/// Scores an existing schedule against the constraints
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints of the schedule
+/// * `config` - The configuration specifying the values used for penalties and the scoring
+/// * `schedule_path` - The csv file of the schedule
+///
+/// # Returns
+/// * `Outcome` - How well the schedule satisfies the constraints
+pub fn check(
+    constraints: &ConstraintStore,
+    config: &Config,
+    schedule_path: &str,
+) -> Result<Outcome> {
+    let schedule = import_schedule(schedule_path, constraints)?;
+    let scoring = Scoring::new(
+        &config.scoring_config,
+        constraints,
+        &config.penalties_config,
+    )?;
+    let (_, total_incurred_penalty) =
+        calculate_penalties(constraints, &schedule, &config.penalties_config);
+    let (_, total_score) = calculate_penalties(
+        constraints,
+        &schedule,
+        &scoring.search_penalties_config(&config.penalties_config)?,
+    );
+
+    constraints.print_schedule_report(&schedule, total_incurred_penalty);
+    print_penalty_report(
+        constraints,
+        &schedule,
+        total_incurred_penalty,
+        &config.penalties_config,
+    );
+    scoring.print_score_report(total_score);
+    explain(constraints, config, &schedule)?;
+
+    let outcome = Outcome::of_schedule(constraints, &schedule, &config.penalties_config);
+    report!(
+        "Total penalty: {} | score: {} | {}",
+        total_incurred_penalty,
+        scoring.format_score(total_score),
+        outcome
+    );
+    Ok(outcome)
+}
+
+/// Writes an existing schedule as a weekly grid to the report output
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints of the schedule
+/// * `config` - The configuration specifying the values used for penalties and the colouring
+/// * `schedule_path` - The csv file of the schedule
+pub fn render(constraints: &ConstraintStore, config: &Config, schedule_path: &str) -> Result<()> {
+    let schedule = import_schedule(schedule_path, constraints)?;
+    print_grid_report(
+        &schedule,
+        constraints,
+        &config.penalties_config,
+        config.report_config.use_colour(is_report_output_terminal()),
+    );
+    Ok(())
+}
+
+/// Edits a schedule interactively in the terminal
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints of the schedule
+/// * `config` - The configuration of the search, the penalties and the exports
+/// * `schedule_path` - The csv file of the schedule to edit. A new schedule is optimised with the
+///   configured search when left out
+pub fn edit(
+    constraints: &ConstraintStore,
+    config: &Config,
+    schedule_path: Option<&str>,
+) -> Result<()> {
+    let schedule = match schedule_path {
+        Some(schedule_path) => import_schedule(schedule_path, constraints)?,
+        None => {
+            let scoring = Scoring::new(
+                &config.scoring_config,
+                constraints,
+                &config.penalties_config,
+            )?;
+            let search_config = scoring.search_config(config)?;
+            let (schedule, _, _) = run_global_search(constraints, &search_config)?;
+            schedule
+        }
+    };
+
+    let mut editor = Editor::new(constraints, config, schedule)?;
+    run_editor(&mut editor).map_err(|error| anyhow!("The editor failed: {}", error))
+}
+
+/// Prints the constraints placed differently in two schedules and how their penalties differ
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints of the schedules
+/// * `config` - The configuration specifying the values used for penalties
+/// * `first_path` - The csv file of the first schedule
+/// * `second_path` - The csv file of the second schedule
+pub fn diff(
+    constraints: &ConstraintStore,
+    config: &Config,
+    first_path: &str,
+    second_path: &str,
+) -> Result<()> {
+    let first = import_schedule(first_path, constraints)?;
+    let second = import_schedule(second_path, constraints)?;
+    let describe_slot = |slot: Option<&Slot>| match slot {
+        Some(slot) => slot.to_string(),
+        None => "unscheduled".to_string(),
+    };
+
+    for constraint in constraints {
+        let first_slot = first.get_scheduled_slot_for_constraint(constraint.id);
+        let second_slot = second.get_scheduled_slot_for_constraint(constraint.id);
+        if first_slot != second_slot {
+            report!(
+                "[{}:{}] {} -> {}",
+                constraint.id,
+                constraint.name.to_uppercase(),
+                describe_slot(first_slot),
+                describe_slot(second_slot)
+            );
+        }
+    }
+
+    let (_, first_penalty) = calculate_penalties(constraints, &first, &config.penalties_config);
+    let (_, second_penalty) = calculate_penalties(constraints, &second, &config.penalties_config);
+    report!(
+        "{} constraints placed differently | penalty {} -> {} ({:+})",
+        first.placement_distance(&second),
+        first_penalty,
+        second_penalty,
+        second_penalty as i64 - first_penalty as i64
+    );
+    Ok(())
+}
+
+/// Tunes the search parameters on the constraints and writes the recommended configuration
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints to tune on
+/// * `config` - The configuration of the solves and the tuning
+pub fn tune(constraints: &ConstraintStore, config: &Config) -> Result<()> {
+    let scoring = Scoring::new(
+        &config.scoring_config,
+        constraints,
+        &config.penalties_config,
+    )?;
+    let tuning_result = run_tuning(constraints, config, &scoring)?;
+    tuning_result.print_report();
+    tuning_result
+        .export_recommended_config(config)
+        .map_err(|error| anyhow!("Could not export the recommended configuration: {}", error))
+}
+
+/// Solves the scenarios of the batch configuration and prints a table comparing them
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints the scenarios are narrowed
+///   from
+/// * `config` - The configuration of the solves and the scenarios
+pub fn batch(constraints: &ConstraintStore, config: &Config) -> Result<()> {
+    let results = run_batch(constraints, config)
+        .map_err(|error| anyhow!("Could not solve the scenarios: {}", error))?;
+    print_comparison_table(&results);
+    if let Some(output_path) = &config.batch_config.output_path {
+        export_comparison(&results, output_path)
+            .map_err(|error| anyhow!("Could not export the comparison to csv: {}", error))?;
+    }
+    Ok(())
+}
+
+/// Prints the JSON Schema of a file or type, or writes it to a file
+///
+/// # Arguments
+/// * `kind` - The file or type to generate the schema of
+/// * `output_path` - The file to write the schema to, instead of standard out
+pub fn schema(kind: SchemaKind, output_path: Option<&Path>) -> Result<()> {
+    let json_string = serde_json::to_string_pretty(&generate_schema(kind))?;
+    match output_path {
+        Some(output_path) => fs::write(output_path, json_string + "\n")
+            .map_err(|error| anyhow!("Could not write the schema to {:?}: {}", output_path, error)),
+        None => {
+            println!("{}", json_string);
+            Ok(())
+        }
+    }
+}
+
+/// Explains the penalties of a schedule when enabled in the configuration
+fn explain(constraints: &ConstraintStore, config: &Config, schedule: &Schedule) -> Result<()> {
+    if !config.explanation_config.enabled {
+        return Ok(());
+    }
+
+    let explanations = explain_schedule(constraints, schedule, &config.penalties_config);
+    print_explanation_report(&explanations, constraints);
+    if let Some(output_path) = &config.explanation_config.output_path {
+        export_explanations(&explanations, output_path)
+            .map_err(|error| anyhow!("Could not export explanations to json: {}", error))?;
+    }
+    Ok(())
+}
+
+/// Imports a schedule from a csv file
+fn import_schedule(schedule_path: &str, constraints: &ConstraintStore) -> Result<Schedule> {
+    Schedule::import_from_csv(schedule_path, constraints).map_err(|error| {
+        anyhow!(
+            "Could not import the schedule {:?}: {}",
+            schedule_path,
+            error
+        )
+    })
+}
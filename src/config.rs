@@ -1,26 +1,95 @@
-use std::{fmt, path::Path};
+use std::{collections::HashSet, fmt, path::Path, time::Duration};
 
 use figment::{
-    Figment,
-    providers::{Format, Serialized, Toml},
+    Figment, Source,
+    providers::{Env, Format, Serialized, Toml},
+    value::Value,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::constraints::{constraint_filter::ConstraintFilter, penalty::Penalty};
+
+/// The prefix of the environment variables overriding configuration values
+const ENV_PREFIX: &str = "SCHEDULER_";
+
+/// A configuration value that failed validation
+///
+/// * `field` - The key of the value, where the keys of nested values are separated by dots
+/// * `message` - Why the value is invalid
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` {}", self.field, self.message)
+    }
+}
+
 #[derive(Debug)]
 pub enum ConfigError {
-    Validation(String),
+    Invalid(Vec<FieldError>),
+    MalformedOverride(String),
+    // key, where the value comes from, why it is invalid
+    InvalidValue(String, String, String),
+    // key, where the value comes from
+    UnknownKey(String, String),
+    UnknownProfile(String),
 }
 
 impl fmt::Display for ConfigError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ConfigError::Validation(msg) => write!(f, "Validation error: {}", msg),
+            ConfigError::Invalid(errors) => {
+                write!(f, "Invalid configuration:")?;
+                for error in errors {
+                    write!(f, "\n  - {}", error)?;
+                }
+                Ok(())
+            }
+            ConfigError::MalformedOverride(config_override) => write!(
+                f,
+                "The override {:?} must be of the form key=value",
+                config_override
+            ),
+            ConfigError::InvalidValue(key, origin, msg) => {
+                write!(f, "Invalid value for `{}` (from {}): {}", key, origin, msg)
+            }
+            ConfigError::UnknownKey(key, origin) => write!(
+                f,
+                "`{}` (from {}) does not match a configuration value",
+                key, origin
+            ),
+            ConfigError::UnknownProfile(profile) => write!(
+                f,
+                "The profile `{}` is not a table of the configuration file",
+                profile
+            ),
         }
     }
 }
 
 impl std::error::Error for ConfigError {}
 
+impl From<figment::Error> for ConfigError {
+    fn from(error: figment::Error) -> Self {
+        let origin = match &error.metadata {
+            Some(metadata) => match &metadata.source {
+                Some(Source::File(path)) => format!("{:?}", path),
+                // NOTE: The defaults never fail to extract, so code sources are the overrides
+                Some(Source::Code(_)) => "a --set override".to_string(),
+                _ => metadata.name.to_string(),
+            },
+            None => "an unknown source".to_string(),
+        };
+
+        ConfigError::InvalidValue(error.path.join("."), origin, error.kind.to_string())
+    }
+}
+
 /// Configuration values for specifying the penalty value for each penalty type used
 ///
 /// Configuration values include:
@@ -38,7 +107,7 @@ impl std::error::Error for ConfigError {}
 ///     is voided
 /// * `gap_low`: The penalty applied for a low priority task when it's configured gap constraint
 ///     is voided
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct PenaltiesConfig {
     pub presence_high: u32,
     pub presence_low: u32,
@@ -65,21 +134,102 @@ impl Default for PenaltiesConfig {
     }
 }
 
+/// The method used for comparing schedules
+///
+/// * `Weighted` - Schedules are compared on the sum of their penalties
+/// * `Lexicographic` - Schedules are compared tier by tier, on the sum of the penalties of each
+///   tier, so no amount of penalties in later tiers outweighs a penalty in an earlier tier
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum ScoringMode {
+    Weighted,
+    Lexicographic,
+}
+
+/// Configuration used for comparing schedules
+///
+/// Configuration includes:
+/// * `mode` - Whether to compare schedules on the weighted sum or lexicographically
+/// * `presence_tier` - The tier of the presence penalties when comparing lexicographically (tier 0
+///   is compared first)
+/// * `allowed_slots_tier` - The tier of the allowed slots penalties
+/// * `preferred_slots_tier` - The tier of the preferred slots penalties
+/// * `gap_tier` - The tier of the gap penalties
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ScoringConfig {
+    pub mode: ScoringMode,
+    pub presence_tier: u32,
+    pub allowed_slots_tier: u32,
+    pub preferred_slots_tier: u32,
+    pub gap_tier: u32,
+}
+
+impl ScoringConfig {
+    /// Returns the tier of a penalty type
+    pub fn tier(&self, penalty: Penalty) -> u32 {
+        match penalty {
+            Penalty::Presence => self.presence_tier,
+            Penalty::AllowedSlots => self.allowed_slots_tier,
+            Penalty::PreferredSlots => self.preferred_slots_tier,
+            Penalty::Gap => self.gap_tier,
+        }
+    }
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig {
+            mode: ScoringMode::Weighted,
+            presence_tier: 1,
+            allowed_slots_tier: 0,
+            preferred_slots_tier: 2,
+            gap_tier: 2,
+        }
+    }
+}
+
+/// The method used for choosing the optimisation strategy applied to a scheduled constraint
+///
+/// * `Fixed` - Strategies are chosen with the configured chances
+/// * `RouletteWheel` - Strategies are chosen with probabilities proportional to weights that start
+///   at the configured chances and move towards the rewards earned by the strategy
+/// * `Bandit` - Strategies are chosen by the UCB1 multi-armed bandit rule, balancing the average
+///   reward earned by each strategy against how rarely it has been tried
+///
+/// Strategies earn a reward of 1 for improving the schedule, 0.1 for an accepted change that did
+/// not improve it and 0 otherwise
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum StrategySelection {
+    Fixed,
+    RouletteWheel,
+    Bandit,
+}
+
 /// Configuration used for specifying the chances for each optimisation strategy within the
 /// make_small_chance function
 ///
 /// NOTE: Any positive number can be used for specifying these chances, the final probabilities are
-/// identified after noramlisation
+/// identified after noramlisation. A strategy with a chance of 0 is never chosen, even when the
+/// strategies are chosen adaptively
 ///
 /// Configuration includes:
 /// * `move_chance` - The chance of move optimisation strategy is chosen
 /// * `unschedule_chance` - The chance of unschedule optimisation strategy is chosen
 /// * `swap_chance` - The chance of swap optimisation strategy is chosen
-#[derive(Serialize, Deserialize, Debug)]
+/// * `selection` - The method used for choosing the strategy
+/// * `reaction_factor` - How quickly the adaptive weights (and bandit reward averages) react to the
+///   rewards, in [0, 1]
+/// * `bandit_exploration` - The exploration constant of the UCB1 rule
+/// * `weight_log_interval` - The number of iterations between recording the adaptive weights in
+///   the optimisation statistics
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct OptimisationStrategyConfig {
     pub move_chance: u32,
     pub unschedule_chance: u32,
     pub swap_chance: u32,
+    pub selection: StrategySelection,
+    pub reaction_factor: f32,
+    pub bandit_exploration: f32,
+    pub weight_log_interval: u32,
 }
 
 impl Default for OptimisationStrategyConfig {
@@ -88,10 +238,687 @@ impl Default for OptimisationStrategyConfig {
             move_chance: 3,
             unschedule_chance: 1,
             swap_chance: 1,
+            selection: StrategySelection::Fixed,
+            reaction_factor: 0.05,
+            bandit_exploration: 0.5,
+            weight_log_interval: 1000,
+        }
+    }
+}
+
+/// Configuration used for limiting the wall-clock time spent optimising
+///
+/// NOTE: The `iterations` limit still applies. Whichever limit is reached first stops the search.
+/// A limit too large to represent as a duration is no limit
+///
+/// Configuration includes:
+/// * `global_search_seconds` - The time allowed for the whole global search (all parallel runs)
+/// * `run_seconds` - The time allowed for each individual run of the global search
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default, Clone)]
+pub struct TimeLimitConfig {
+    pub global_search_seconds: Option<f32>,
+    pub run_seconds: Option<f32>,
+}
+
+impl TimeLimitConfig {
+    /// Returns the time limit for the whole global search, if it is set and representable
+    pub fn global_search_limit(&self) -> Option<Duration> {
+        self.global_search_seconds
+            .and_then(|seconds| Duration::try_from_secs_f32(seconds).ok())
+    }
+
+    /// Returns the time limit for a single run of the global search, if it is set and
+    /// representable
+    pub fn run_limit(&self) -> Option<Duration> {
+        self.run_seconds
+            .and_then(|seconds| Duration::try_from_secs_f32(seconds).ok())
+    }
+}
+
+/// The cooling schedule used for lowering the temperature of the annealing algorithm
+///
+/// * `Geometric` - The temperature is multiplied by `cooling_factor` every iteration
+/// * `Linear` - The temperature decreases by the same amount every iteration, reaching zero at the
+///   final iteration
+/// * `Logarithmic` - The temperature follows `initial_temperature / ln(e + iteration)`
+/// * `LundyMees` - The temperature follows `t / (1 + lundy_mees_beta * t)`
+/// * `Adaptive` - Geometric cooling that reheats when the acceptance ratio drops below a target
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum CoolingSchedule {
+    Geometric,
+    Linear,
+    Logarithmic,
+    LundyMees,
+    Adaptive,
+}
+
+/// The policy applied when the annealing algorithm stagnates
+///
+/// * `Never` - The algorithm is never restarted
+/// * `ResetTemperature` - The temperature is reset, the search carries on from the current schedule
+/// * `RestartFromBest` - The temperature is reset and the search carries on from the best schedule
+///   found so far
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum RestartPolicy {
+    Never,
+    ResetTemperature,
+    RestartFromBest,
+}
+
+/// Configuration used for specifying how the temperature of the annealing algorithm evolves
+///
+/// Configuration includes:
+/// * `cooling_schedule` - The cooling schedule used for lowering the temperature
+/// * `lundy_mees_beta` - The beta parameter of the Lundy-Mees cooling schedule
+/// * `adaptive_window` - The number of evaluated changes over which the acceptance ratio is
+///   measured by the adaptive cooling schedule
+/// * `adaptive_target_acceptance_ratio` - The acceptance ratio below which the adaptive cooling
+///   schedule reheats
+/// * `adaptive_reheat_factor` - The factor the temperature is multiplied by when reheating (capped
+///   at the initial temperature)
+/// * `restart_policy` - The policy applied when the algorithm stagnates
+/// * `stagnation_threshold` - The number of non-accepted iterations after which the restart policy
+///   is applied
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct AnnealingConfig {
+    pub cooling_schedule: CoolingSchedule,
+    pub lundy_mees_beta: f32,
+    pub adaptive_window: u32,
+    pub adaptive_target_acceptance_ratio: f32,
+    pub adaptive_reheat_factor: f32,
+    pub restart_policy: RestartPolicy,
+    pub stagnation_threshold: u32,
+}
+
+impl Default for AnnealingConfig {
+    fn default() -> Self {
+        AnnealingConfig {
+            cooling_schedule: CoolingSchedule::Geometric,
+            lundy_mees_beta: 0.001,
+            adaptive_window: 100,
+            adaptive_target_acceptance_ratio: 0.05,
+            adaptive_reheat_factor: 1.5,
+            restart_policy: RestartPolicy::ResetTemperature,
+            stagnation_threshold: 500,
+        }
+    }
+}
+
+/// The criterion used for deciding whether a change to the schedule is accepted
+///
+/// * `Metropolis` - Simulated annealing. Worse schedules are accepted with a probability that
+///   decreases with the temperature
+/// * `LateAcceptance` - Late acceptance hill climbing. A schedule is accepted if it is no worse than
+///   the current schedule or the schedule from `late_acceptance_history_length` iterations ago
+/// * `ThresholdAccepting` - A schedule is accepted if it is worse by no more than the current
+///   temperature, which is used as the threshold
+/// * `GreatDeluge` - A schedule is accepted if its penalty is below a water level that starts at
+///   the initial penalty and is lowered by `great_deluge_rain_speed` every iteration
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum AcceptanceCriterion {
+    Metropolis,
+    LateAcceptance,
+    ThresholdAccepting,
+    GreatDeluge,
+}
+
+/// Configuration used for specifying how changes to the schedule are accepted
+///
+/// Configuration includes:
+/// * `criterion` - The acceptance criterion
+/// * `late_acceptance_history_length` - The number of past penalties remembered by late acceptance
+///   hill climbing
+/// * `great_deluge_rain_speed` - The amount the great deluge water level is lowered by every
+///   iteration
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct AcceptanceConfig {
+    pub criterion: AcceptanceCriterion,
+    pub late_acceptance_history_length: u32,
+    pub great_deluge_rain_speed: f32,
+}
+
+impl Default for AcceptanceConfig {
+    fn default() -> Self {
+        AcceptanceConfig {
+            criterion: AcceptanceCriterion::Metropolis,
+            late_acceptance_history_length: 50,
+            great_deluge_rain_speed: 0.01,
+        }
+    }
+}
+
+/// The algorithm used by the global search
+///
+/// * `IndependentRuns` - Runs independent annealing runs in parallel and keeps the best result
+/// * `ParallelTempering` - Runs chains at fixed temperatures in parallel, periodically exchanging
+///   the schedules of chains at neighbouring temperatures
+/// * `Islands` - Runs annealing runs (islands) in parallel, periodically restarting the weakest
+///   islands from the best schedule found across all islands
+/// * `Genetic` - Evolves a population of schedules using selection, crossover and mutation
+/// * `LargeNeighbourhood` - Repeatedly ruins a large part of the schedule and recreates it, choosing
+///   the ruin operators adaptively
+/// * `Exact` - A branch and bound search that proves the optimum of small instances, or reports
+///   the best schedule found with a lower bound when stopped early
+/// * `MultiObjective` - Runs Pareto annealing runs in parallel, keeping an archive of the schedules
+///   that are not dominated on the chosen penalty types
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    IndependentRuns,
+    ParallelTempering,
+    Islands,
+    Genetic,
+    LargeNeighbourhood,
+    Exact,
+    MultiObjective,
+}
+
+/// Configuration used for the parallel tempering search mode
+///
+/// NOTE: Each chain accepts changes using the Metropolis criterion at its own fixed temperature,
+/// the cooling schedule, restart policy and acceptance criterion are not used by this mode
+///
+/// Configuration includes:
+/// * `temperatures` - The temperature of each chain. If empty, `number_of_global_searches` chains
+///   are used with temperatures spaced geometrically between `min_temperature` and
+///   `max_temperature`
+/// * `min_temperature` - The temperature of the coldest chain
+/// * `max_temperature` - The temperature of the hottest chain
+/// * `exchange_interval` - The number of iterations between attempts to exchange schedules
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ParallelTemperingConfig {
+    pub temperatures: Vec<f32>,
+    pub min_temperature: f32,
+    pub max_temperature: f32,
+    pub exchange_interval: u32,
+}
+
+impl Default for ParallelTemperingConfig {
+    fn default() -> Self {
+        ParallelTemperingConfig {
+            temperatures: Vec::new(),
+            min_temperature: 1.0,
+            max_temperature: 200.0,
+            exchange_interval: 100,
+        }
+    }
+}
+
+impl ParallelTemperingConfig {
+    /// Returns the temperature of each chain, ordered from the coldest to the hottest
+    ///
+    /// # Arguments
+    /// * `number_of_chains` - The number of chains used when no temperatures are listed explicitly
+    pub fn temperature_ladder(&self, number_of_chains: u32) -> Vec<f32> {
+        let mut temperatures = if !self.temperatures.is_empty() {
+            self.temperatures.clone()
+        } else if number_of_chains <= 1 {
+            vec![self.min_temperature]
+        } else {
+            let ratio = (self.max_temperature / self.min_temperature)
+                .powf(1.0 / (number_of_chains - 1) as f32);
+            (0..number_of_chains)
+                .map(|i| self.min_temperature * ratio.powi(i as i32))
+                .collect()
+        };
+
+        temperatures.sort_by(|a, b| a.total_cmp(b));
+        temperatures
+    }
+}
+
+/// The schedule the weakest islands restart from when migrating
+///
+/// * `GlobalBest` - A copy of the best schedule found across all islands
+/// * `PerturbedGlobalBest` - A copy of the best schedule found across all islands with
+///   `perturbation_size` randomly chosen tasks moved
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum MigrationPolicy {
+    GlobalBest,
+    PerturbedGlobalBest,
+}
+
+/// Configuration used for the island search mode
+///
+/// Configuration includes:
+/// * `migration_interval` - The number of iterations between migrations
+/// * `migration_policy` - The schedule the weakest islands restart from
+/// * `restarted_islands` - The number of weakest islands restarted on every migration
+/// * `perturbation_size` - The number of tasks moved by the `PerturbedGlobalBest` policy
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct IslandConfig {
+    pub migration_interval: u32,
+    pub migration_policy: MigrationPolicy,
+    pub restarted_islands: u32,
+    pub perturbation_size: u32,
+}
+
+impl Default for IslandConfig {
+    fn default() -> Self {
+        IslandConfig {
+            migration_interval: 1000,
+            migration_policy: MigrationPolicy::PerturbedGlobalBest,
+            restarted_islands: 1,
+            perturbation_size: 3,
         }
     }
 }
 
+/// The crossover used for combining two parent schedules into a child schedule
+///
+/// * `DayBlocks` - Each day of the child is copied from one of the two parents
+/// * `ConstraintPlacements` - Each constraint of the child is placed where one of the two parents
+///   placed it
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum CrossoverOperator {
+    DayBlocks,
+    ConstraintPlacements,
+}
+
+/// Configuration used for the genetic search mode
+///
+/// Configuration includes:
+/// * `population_size` - The number of schedules in the population
+/// * `generations` - The number of generations to evolve the population for
+/// * `crossover_operator` - The crossover used for combining parents
+/// * `crossover_rate` - The probability of a child being bred by crossover rather than copied from
+///   its first parent
+/// * `mutation_rate` - The probability of a child being mutated by one of the optimisation
+///   strategies
+/// * `elitism` - The number of best schedules carried over unchanged to the next generation
+/// * `tournament_size` - The number of schedules competing in each tournament selection
+/// * `hybrid_annealing_iterations` - The number of annealing iterations used to refine every
+///   child. `0` disables the refinement
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct GeneticConfig {
+    pub population_size: u32,
+    pub generations: u32,
+    pub crossover_operator: CrossoverOperator,
+    pub crossover_rate: f32,
+    pub mutation_rate: f32,
+    pub elitism: u32,
+    pub tournament_size: u32,
+    pub hybrid_annealing_iterations: u32,
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        GeneticConfig {
+            population_size: 50,
+            generations: 200,
+            crossover_operator: CrossoverOperator::DayBlocks,
+            crossover_rate: 0.8,
+            mutation_rate: 0.3,
+            elitism: 2,
+            tournament_size: 3,
+            hybrid_annealing_iterations: 0,
+        }
+    }
+}
+
+/// The method used for rescheduling the constraints unscheduled by a ruin operator
+///
+/// * `Greedy` - Each constraint is scheduled, one at a time, in the slot that increases the penalty
+///   the least, or left unscheduled if every slot would increase the penalty
+/// * `Exact` - A branch and bound search over the best candidate slots of each constraint. Falls
+///   back to `Greedy` when there are more than `exact_max_constraints` constraints to schedule
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum RecreateMethod {
+    Greedy,
+    Exact,
+}
+
+/// Configuration used for the large neighbourhood search mode
+///
+/// The ruin operators (a whole day, a time band across all days, all constraints of one type) are
+/// chosen with probabilities proportional to their weights. After every iteration the weight of
+/// the chosen operator moves towards the reward it earned, by `reaction_factor`
+///
+/// Configuration includes:
+/// * `recreate_method` - The method used for rescheduling the ruined constraints
+/// * `time_band_width` - The number of slots ruined by the time band operator on every day
+/// * `exact_max_constraints` - The maximum number of constraints the exact method schedules
+/// * `exact_candidate_slots` - The number of best candidate slots considered for each constraint by
+///   the exact method
+/// * `exact_node_limit` - The maximum number of nodes explored by the exact method
+/// * `reaction_factor` - How quickly the operator weights react to the rewards, in [0, 1]
+/// * `global_best_reward` - The reward for finding a new best schedule
+/// * `improvement_reward` - The reward for improving on the current schedule
+/// * `acceptance_reward` - The reward for a worse schedule that was still accepted
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct LargeNeighbourhoodConfig {
+    pub recreate_method: RecreateMethod,
+    pub time_band_width: u8,
+    pub exact_max_constraints: u32,
+    pub exact_candidate_slots: u32,
+    pub exact_node_limit: u32,
+    pub reaction_factor: f32,
+    pub global_best_reward: f32,
+    pub improvement_reward: f32,
+    pub acceptance_reward: f32,
+}
+
+impl Default for LargeNeighbourhoodConfig {
+    fn default() -> Self {
+        LargeNeighbourhoodConfig {
+            recreate_method: RecreateMethod::Greedy,
+            time_band_width: 8,
+            exact_max_constraints: 5,
+            exact_candidate_slots: 6,
+            exact_node_limit: 10000,
+            reaction_factor: 0.1,
+            global_best_reward: 33.0,
+            improvement_reward: 9.0,
+            acceptance_reward: 13.0,
+        }
+    }
+}
+
+/// Configuration used for the exact search mode
+///
+/// The search stops at the first of the node limit, its own time limit and the time limit of the
+/// global search. When stopped early, the best schedule found is returned with a lower bound on
+/// the optimal penalty
+///
+/// Configuration includes:
+/// * `node_limit` - The maximum number of nodes explored by the branch and bound search
+/// * `time_limit_seconds` - The wall-clock time limit of the branch and bound search. A limit too
+///   large to represent as a duration is no limit
+/// * `polish_annealing_result` - Whether to first run the independent annealing runs and start the
+///   branch and bound search from their best schedule
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ExactSearchConfig {
+    pub node_limit: u64,
+    pub time_limit_seconds: Option<f32>,
+    pub polish_annealing_result: bool,
+}
+
+impl ExactSearchConfig {
+    /// Returns the time limit of the branch and bound search, if it is set and representable
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit_seconds
+            .and_then(|seconds| Duration::try_from_secs_f32(seconds).ok())
+    }
+}
+
+impl Default for ExactSearchConfig {
+    fn default() -> Self {
+        ExactSearchConfig {
+            node_limit: 1_000_000,
+            time_limit_seconds: None,
+            polish_annealing_result: false,
+        }
+    }
+}
+
+/// Configuration used for the multi-objective search mode
+///
+/// Each penalty type chosen as an objective is scored separately, as the sum of that penalty over
+/// all constraints. A schedule dominates another if it scores no worse on every objective and
+/// better on at least one
+///
+/// Configuration includes:
+/// * `objectives` - The penalty types to optimise as separate objectives
+/// * `archive_size` - The maximum number of non-dominated schedules kept. When full, the schedules
+///   in the most crowded part of the front are dropped first
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct MultiObjectiveConfig {
+    pub objectives: Vec<Penalty>,
+    pub archive_size: u32,
+}
+
+impl Default for MultiObjectiveConfig {
+    fn default() -> Self {
+        MultiObjectiveConfig {
+            objectives: vec![
+                Penalty::Presence,
+                Penalty::AllowedSlots,
+                Penalty::PreferredSlots,
+                Penalty::Gap,
+            ],
+            archive_size: 20,
+        }
+    }
+}
+
+/// Configuration used for the analysis run before optimisation
+///
+/// Configuration includes:
+/// * `enabled` - Whether to run the analysis
+/// * `strict` - Whether to exit with an error instead of optimising when the constraints are
+///   detected to be infeasible
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct PresolveConfig {
+    pub enabled: bool,
+    pub strict: bool,
+}
+
+impl Default for PresolveConfig {
+    fn default() -> Self {
+        PresolveConfig {
+            enabled: true,
+            strict: false,
+        }
+    }
+}
+
+/// Configuration used for explaining the penalties of the constraints in the final schedule
+///
+/// Configuration includes:
+/// * `enabled` - Whether to explain the unscheduled and penalised constraints after optimisation
+/// * `output_path` - Optional path of a json file to export the explanations to
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ExplanationConfig {
+    pub enabled: bool,
+    pub output_path: Option<String>,
+}
+
+impl Default for ExplanationConfig {
+    fn default() -> Self {
+        ExplanationConfig {
+            enabled: true,
+            output_path: None,
+        }
+    }
+}
+
+/// Configuration used for keeping diverse alternatives to the best schedule
+///
+/// Configuration includes:
+/// * `count` - The number of schedules to return, including the best schedule (1 returns only the
+///   best schedule)
+/// * `min_distance` - The minimum number of constraints that must be placed differently between
+///   any two of the returned schedules
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct AlternativesConfig {
+    pub count: u32,
+    pub min_distance: u32,
+}
+
+impl Default for AlternativesConfig {
+    fn default() -> Self {
+        AlternativesConfig {
+            count: 1,
+            min_distance: 3,
+        }
+    }
+}
+
+/// Configuration used for tuning the parameters of the search (`scheduler tune`)
+///
+/// NOTE: Every solve of the tuning uses the configured search mode and time limits. The global
+/// search time limit of a solve is shortened when the time budget would otherwise be exceeded
+///
+/// Configuration includes:
+/// * `time_budget_seconds` - The wall-clock time allowed for the whole tuning. A budget too large
+///   to represent as a duration is no budget
+/// * `candidates` - The number of parameter configurations raced against each other, including the
+///   configured parameters
+/// * `max_rounds` - The maximum number of solves of each configuration
+/// * `min_rounds` - The number of solves of each configuration before any is eliminated
+/// * `elimination_threshold` - How many standard errors worse than the best configuration the
+///   mean penalty of a configuration must be for it to be eliminated
+/// * `temperature_range` - The range initial temperatures are drawn from
+/// * `cooling_factor_range` - The range cooling factors are drawn from
+/// * `iterations_range` - The range iteration counts are drawn from
+/// * `max_strategy_chance` - The largest chance drawn for each optimisation strategy
+/// * `output_path` - The path the recommended configuration is written to
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct TuningConfig {
+    pub time_budget_seconds: f32,
+    pub candidates: u32,
+    pub max_rounds: u32,
+    pub min_rounds: u32,
+    pub elimination_threshold: f32,
+    pub temperature_range: (f32, f32),
+    pub cooling_factor_range: (f32, f32),
+    pub iterations_range: (u32, u32),
+    pub max_strategy_chance: u32,
+    pub output_path: String,
+}
+
+impl TuningConfig {
+    /// Returns the time allowed for the whole tuning, if it is representable
+    pub fn time_budget(&self) -> Option<Duration> {
+        Duration::try_from_secs_f32(self.time_budget_seconds).ok()
+    }
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        TuningConfig {
+            time_budget_seconds: 300.0,
+            candidates: 16,
+            max_rounds: 10,
+            min_rounds: 3,
+            elimination_threshold: 2.0,
+            temperature_range: (1.0, 1000.0),
+            cooling_factor_range: (0.99, 0.9999),
+            iterations_range: (10000, 200000),
+            max_strategy_chance: 5,
+            output_path: "./config.tuned.toml".to_string(),
+        }
+    }
+}
+
+/// When the reports are coloured
+///
+/// * `Auto` - Only when the reports are written to a terminal and `NO_COLOR` is not set
+/// * `Always` - Always, including in report files
+/// * `Never` - Never
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq)]
+pub enum ColourMode {
+    Auto,
+    Always,
+    Never,
+}
+
+/// Configuration used for writing the reports, which are written regardless of the log level
+///
+/// Configuration includes:
+/// * `output_path` - Optional path of a file to write the reports to, instead of standard out
+/// * `grid` - Whether to render the schedule as a week grid after optimisation
+/// * `colour` - When to colour the week grid
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ReportConfig {
+    pub output_path: Option<String>,
+    pub grid: bool,
+    pub colour: ColourMode,
+}
+
+impl ReportConfig {
+    /// Checks whether the week grid is coloured
+    ///
+    /// # Arguments
+    /// * `is_terminal` - Whether the reports are written to a terminal
+    pub fn use_colour(&self, is_terminal: bool) -> bool {
+        match self.colour {
+            ColourMode::Auto => is_terminal && std::env::var_os("NO_COLOR").is_none(),
+            ColourMode::Always => true,
+            ColourMode::Never => false,
+        }
+    }
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        ReportConfig {
+            output_path: None,
+            grid: true,
+            colour: ColourMode::Auto,
+        }
+    }
+}
+
+/// Configuration used for narrowing the constraints to solve
+///
+/// Selectors are written as `tag:<tag>`, `type:<name>` or `id:<id>`
+///
+/// Configuration includes:
+/// * `include` - The selectors of the constraints to keep. All constraints are kept when empty
+/// * `exclude` - The selectors of the constraints to remove from the kept constraints
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct FilterConfig {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+impl FilterConfig {
+    /// Parses the selectors of the filter
+    ///
+    /// # Returns
+    /// * `ConstraintFilter` - The filter
+    /// * `String` - Why a selector is invalid
+    pub fn filter(&self) -> Result<ConstraintFilter, String> {
+        ConstraintFilter::parse(&self.include, &self.exclude)
+    }
+}
+
+/// A named scenario of the batch mode, solved on the constraints kept by its filter
+///
+/// Configuration includes:
+/// * `name` - The name of the scenario in the comparison table and the exported schedule
+/// * `filter` - The selectors narrowing the constraints of the scenario, applied after
+///   `filter_config`
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ScenarioConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub filter: FilterConfig,
+}
+
+/// The name of the scenario of the batch mode keeping all constraints
+pub const BASELINE_SCENARIO: &str = "baseline";
+
+/// Turns the name of a scenario into the part of the file name of its exported schedule, replacing
+/// every character other than letters, digits, dashes and underscores with an underscore
+pub fn scenario_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Configuration used for solving several scenarios in one go (`scheduler batch`)
+///
+/// NOTE: A baseline scenario keeping all constraints is always solved first, so every scenario
+/// can be compared with it
+///
+/// Configuration includes:
+/// * `scenarios` - The scenarios to solve, in order
+/// * `output_path` - Optional path of a csv file to export the comparison table to
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct BatchConfig {
+    pub scenarios: Vec<ScenarioConfig>,
+    pub output_path: Option<String>,
+}
+
 /// Application configuration read at startup from config.toml file
 ///
 /// If no toml file is provided, applies the default values in the configuration
@@ -99,23 +926,67 @@ impl Default for OptimisationStrategyConfig {
 /// Configuration values include:
 /// * `iterations` - The number of iterations to run the local search algorithm
 /// * `number_of_global_searches` - The number of global search algorithms to run in parallel
-/// * `constraint_file_path` - The path to the constraints.json file
+/// * `constraint_file_path` - The path to the constraints.json file, or to a .toml file written in
+///   the human-friendly constraint format
+/// * `constraint_include_paths` - The paths to more constraint files merged into the constraints,
+///   renumbered after the constraints of `constraint_file_path`
 /// * `initial_temperature` - The initial temperature for the local search algorithm
 /// * `cooling_factor` - The cooling factor for the local search algorithm
 /// * `random_seed` - The random seed used for generating quasi random schedules
+/// * `scoring_config` - Whether schedules are compared on the weighted sum of their penalties or
+///   lexicographically by tier
+/// * `time_limit_config` - The wall-clock time limits for the global search and each of its runs
+/// * `annealing_config` - The cooling schedule and restart policy of the local search algorithm
+/// * `acceptance_config` - The acceptance criterion of the local search algorithm
+/// * `search_mode` - The algorithm used by the global search
+/// * `parallel_tempering_config` - The temperature ladder and exchange interval used by the
+///   parallel tempering search mode
+/// * `island_config` - The migration interval and policy used by the island search mode
+/// * `genetic_config` - The population, crossover and mutation settings used by the genetic search
+///   mode
+/// * `large_neighbourhood_config` - The recreate method and operator weighting used by the large
+///   neighbourhood search mode
+/// * `exact_search_config` - The limits of the exact search mode
+/// * `multi_objective_config` - The objectives and archive size of the multi-objective search mode
+/// * `presolve_config` - Whether to analyse the constraints before optimisation
+/// * `explanation_config` - Whether and where to explain the penalties of the final schedule
+/// * `alternatives_config` - The number of diverse alternative schedules to return
+/// * `tuning_config` - The time budget and parameter ranges of the parameter tuning
+/// * `report_config` - Where the reports are written to and whether the schedule is rendered
+/// * `filter_config` - The selectors narrowing the constraints to solve
+/// * `batch_config` - The scenarios solved and compared by the batch mode
 /// * `output_path` - The path to output the generated schedule to, include the name of the output
 /// csv file as well (e.g. ../schedule.csv)
 /// * `debug` - Whether to run in debug mode or not
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct Config {
     pub iterations: u32,
     pub number_of_global_searches: u32,
     pub constraint_file_path: String,
+    pub constraint_include_paths: Vec<String>,
     pub initial_temperature: f32,
     pub cooling_factor: f32,
     pub random_seed: u32,
     pub penalties_config: PenaltiesConfig,
+    pub scoring_config: ScoringConfig,
     pub optimisation_strategy_config: OptimisationStrategyConfig,
+    pub time_limit_config: TimeLimitConfig,
+    pub annealing_config: AnnealingConfig,
+    pub acceptance_config: AcceptanceConfig,
+    pub search_mode: SearchMode,
+    pub parallel_tempering_config: ParallelTemperingConfig,
+    pub island_config: IslandConfig,
+    pub genetic_config: GeneticConfig,
+    pub large_neighbourhood_config: LargeNeighbourhoodConfig,
+    pub exact_search_config: ExactSearchConfig,
+    pub multi_objective_config: MultiObjectiveConfig,
+    pub presolve_config: PresolveConfig,
+    pub explanation_config: ExplanationConfig,
+    pub alternatives_config: AlternativesConfig,
+    pub tuning_config: TuningConfig,
+    pub report_config: ReportConfig,
+    pub filter_config: FilterConfig,
+    pub batch_config: BatchConfig,
     pub output_path: String,
     pub debug: bool,
 }
@@ -127,11 +998,30 @@ impl Default for Config {
             iterations: 1000,
             number_of_global_searches: 5,
             constraint_file_path: "../constraints.json".to_string(),
+            constraint_include_paths: Vec::new(),
             initial_temperature: 200.0,
             cooling_factor: 0.999,
             random_seed: 0,
             penalties_config: PenaltiesConfig::default(),
+            scoring_config: ScoringConfig::default(),
             optimisation_strategy_config: OptimisationStrategyConfig::default(),
+            time_limit_config: TimeLimitConfig::default(),
+            annealing_config: AnnealingConfig::default(),
+            acceptance_config: AcceptanceConfig::default(),
+            search_mode: SearchMode::IndependentRuns,
+            parallel_tempering_config: ParallelTemperingConfig::default(),
+            island_config: IslandConfig::default(),
+            genetic_config: GeneticConfig::default(),
+            large_neighbourhood_config: LargeNeighbourhoodConfig::default(),
+            exact_search_config: ExactSearchConfig::default(),
+            multi_objective_config: MultiObjectiveConfig::default(),
+            presolve_config: PresolveConfig::default(),
+            explanation_config: ExplanationConfig::default(),
+            alternatives_config: AlternativesConfig::default(),
+            tuning_config: TuningConfig::default(),
+            report_config: ReportConfig::default(),
+            filter_config: FilterConfig::default(),
+            batch_config: BatchConfig::default(),
             output_path: "../".to_string(),
             debug: true,
         }
@@ -139,23 +1029,434 @@ impl Default for Config {
 }
 
 impl Config {
-    pub fn new() -> Result<Config, ConfigError> {
-        let config: Config = Figment::new()
-            .merge(Serialized::defaults(Config::default()))
-            .merge(Toml::file("config.toml"))
-            .extract()
-            .unwrap();
+    /// Reads the configuration from a toml file, applying a profile and overrides on top of it
+    ///
+    /// The values are merged in the following order, the later ones taking precedence:
+    /// 1. The default values
+    /// 2. The values of the toml file
+    /// 3. The values of the selected profile, a table of the toml file named after the profile
+    ///    (e.g. `[quick]` and `[quick.annealing_config]`)
+    /// 4. The `SCHEDULER_*` environment variables, where the keys of nested values are separated
+    ///    by double underscores (e.g. `SCHEDULER_PENALTIES_CONFIG__PRESENCE_HIGH=20`)
+    /// 5. The `key=value` overrides, where the keys of nested values are separated by dots (e.g.
+    ///    `annealing_config.cooling_schedule=Linear`)
+    ///
+    /// # Arguments
+    /// * `config_path` - The path to the toml file. The default values are used for the values it
+    ///   does not specify, or for all values if it does not exist
+    /// * `profile` - The name of the profile to apply, if any
+    /// * `overrides` - The `key=value` overrides of configuration values
+    ///
+    /// # Returns
+    /// * `Config` - The configuration
+    /// * `ConfigError` - If a value does not match a configuration value or is invalid, or the
+    ///   profile does not exist
+    pub fn new(
+        config_path: &Path,
+        profile: Option<&str>,
+        overrides: &[String],
+    ) -> Result<Config, ConfigError> {
+        let defaults = Figment::from(Serialized::defaults(Config::default()));
+        let config_file = Figment::from(Toml::file(config_path));
+        let mut figment = defaults.clone().merge(config_file.clone());
+
+        if let Some(profile) = profile {
+            let is_profile = matches!(config_file.find_value(profile), Ok(Value::Dict(..)))
+                && defaults.find_value(profile).is_err();
+            if !is_profile {
+                return Err(ConfigError::UnknownProfile(profile.to_string()));
+            }
+            figment = figment.merge(config_file.focus(profile));
+        }
+
+        // NOTE: SCHEDULER_PROFILE selects the profile rather than overriding a value
+        let env = Env::prefixed(ENV_PREFIX).split("__").ignore(&["profile"]);
+        for (key, _) in env.iter() {
+            if defaults.find_value(key.as_str()).is_err() {
+                return Err(ConfigError::UnknownKey(
+                    key.as_str().to_string(),
+                    format!("`{}` environment variables", ENV_PREFIX),
+                ));
+            }
+        }
+        figment = figment.merge(env);
+
+        for config_override in overrides {
+            let Some((key, value)) = config_override.split_once('=') else {
+                return Err(ConfigError::MalformedOverride(config_override.clone()));
+            };
+
+            let key = key.trim();
+            if defaults.find_value(key).is_err() {
+                return Err(ConfigError::UnknownKey(
+                    key.to_string(),
+                    "a --set override".to_string(),
+                ));
+            }
+
+            // NOTE: Values are parsed like environment variables, so numbers, booleans, arrays
+            // (e.g. [1, 2]) and strings are all accepted without quoting
+            let Ok(value) = value.trim().parse::<Value>();
+            figment = figment.merge(Serialized::default(key, value));
+        }
+
+        let config: Config = figment.extract()?;
 
         config.validate()?;
         Ok(config)
     }
 
-    // TODO: Add more validation logic
+    /// Returns the paths of all constraint files to merge, starting with `constraint_file_path`
+    pub fn constraint_file_paths(&self) -> Vec<String> {
+        let mut constraint_file_paths = vec![self.constraint_file_path.clone()];
+        constraint_file_paths.extend(self.constraint_include_paths.iter().cloned());
+        constraint_file_paths
+    }
+
+    /// Validates every value of the configuration
+    ///
+    /// # Returns
+    /// * `ConfigError::Invalid` - With an error for each invalid value, if any value is invalid
     fn validate(&self) -> Result<(), ConfigError> {
-        if Path::new(&self.output_path).file_name().is_none() {
-            return Err(ConfigError::Validation("The specified output directory is invalid. Please ensure the file name is also specified".to_string()));
+        let mut errors: Vec<FieldError> = Vec::new();
+        let mut check = |is_valid: bool, field: &str, message: &str| {
+            if !is_valid {
+                errors.push(FieldError {
+                    field: field.to_string(),
+                    message: message.to_string(),
+                });
+            }
+        };
+
+        check(self.iterations > 0, "iterations", "must be at least 1");
+        check(
+            self.number_of_global_searches > 0,
+            "number_of_global_searches",
+            "must be at least 1",
+        );
+        check(
+            Path::new(&self.constraint_file_path).is_file(),
+            "constraint_file_path",
+            "must be an existing file",
+        );
+        check(
+            self.constraint_include_paths
+                .iter()
+                .all(|path| Path::new(path).is_file()),
+            "constraint_include_paths",
+            "must all be existing files",
+        );
+        check(
+            is_positive(self.initial_temperature),
+            "initial_temperature",
+            "must be positive",
+        );
+        check(
+            self.cooling_factor > 0.0 && self.cooling_factor < 1.0,
+            "cooling_factor",
+            "must be between 0 and 1 (exclusive)",
+        );
+        check(
+            Path::new(&self.output_path).file_name().is_some(),
+            "output_path",
+            "must include the name of the output csv file (e.g. ../schedule.csv)",
+        );
+        check(
+            has_existing_directory(&self.output_path),
+            "output_path",
+            "must be in an existing directory",
+        );
+
+        let scoring_config = &self.scoring_config;
+        for (tier, field) in [
+            (scoring_config.presence_tier, "scoring_config.presence_tier"),
+            (scoring_config.allowed_slots_tier, "scoring_config.allowed_slots_tier"),
+            (scoring_config.preferred_slots_tier, "scoring_config.preferred_slots_tier"),
+            (scoring_config.gap_tier, "scoring_config.gap_tier"),
+        ] {
+            check(tier <= 3, field, "must be between 0 and 3");
+        }
+
+        let optimisation_strategy_config = &self.optimisation_strategy_config;
+        let total_chance = optimisation_strategy_config
+            .move_chance
+            .checked_add(optimisation_strategy_config.unschedule_chance)
+            .and_then(|chance| chance.checked_add(optimisation_strategy_config.swap_chance));
+        check(
+            total_chance != Some(0),
+            "optimisation_strategy_config",
+            "must have at least one positive strategy chance",
+        );
+        check(
+            total_chance.is_some(),
+            "optimisation_strategy_config",
+            "must have strategy chances adding up to at most 4294967295",
+        );
+        check(
+            is_ratio(optimisation_strategy_config.reaction_factor),
+            "optimisation_strategy_config.reaction_factor",
+            "must be between 0 and 1",
+        );
+        check(
+            optimisation_strategy_config.bandit_exploration >= 0.0,
+            "optimisation_strategy_config.bandit_exploration",
+            "must not be negative",
+        );
+
+        for (seconds, field) in [
+            (
+                self.time_limit_config.global_search_seconds,
+                "time_limit_config.global_search_seconds",
+            ),
+            (self.time_limit_config.run_seconds, "time_limit_config.run_seconds"),
+            (
+                self.exact_search_config.time_limit_seconds,
+                "exact_search_config.time_limit_seconds",
+            ),
+        ] {
+            check(
+                seconds.is_none_or(is_positive),
+                field,
+                "must be a positive number of seconds",
+            );
+        }
+
+        let annealing_config = &self.annealing_config;
+        check(
+            annealing_config.lundy_mees_beta >= 0.0,
+            "annealing_config.lundy_mees_beta",
+            "must not be negative",
+        );
+        check(
+            annealing_config.adaptive_window > 0,
+            "annealing_config.adaptive_window",
+            "must be at least 1",
+        );
+        check(
+            is_ratio(annealing_config.adaptive_target_acceptance_ratio),
+            "annealing_config.adaptive_target_acceptance_ratio",
+            "must be between 0 and 1",
+        );
+        check(
+            annealing_config.adaptive_reheat_factor >= 1.0,
+            "annealing_config.adaptive_reheat_factor",
+            "must be at least 1",
+        );
+
+        let acceptance_config = &self.acceptance_config;
+        check(
+            acceptance_config.late_acceptance_history_length > 0,
+            "acceptance_config.late_acceptance_history_length",
+            "must be at least 1",
+        );
+        check(
+            acceptance_config.great_deluge_rain_speed >= 0.0,
+            "acceptance_config.great_deluge_rain_speed",
+            "must not be negative",
+        );
+
+        check(
+            self.parallel_tempering_config
+                .temperature_ladder(self.number_of_global_searches)
+                .into_iter()
+                .all(is_positive),
+            "parallel_tempering_config",
+            "must have positive temperatures",
+        );
+
+        let genetic_config = &self.genetic_config;
+        check(
+            genetic_config.population_size >= 2,
+            "genetic_config.population_size",
+            "must be at least 2",
+        );
+        check(
+            genetic_config.tournament_size > 0,
+            "genetic_config.tournament_size",
+            "must be at least 1",
+        );
+        check(
+            genetic_config.elitism < genetic_config.population_size,
+            "genetic_config.elitism",
+            "must be smaller than the population size",
+        );
+        check(
+            is_ratio(genetic_config.crossover_rate),
+            "genetic_config.crossover_rate",
+            "must be between 0 and 1",
+        );
+        check(
+            is_ratio(genetic_config.mutation_rate),
+            "genetic_config.mutation_rate",
+            "must be between 0 and 1",
+        );
+
+        let large_neighbourhood_config = &self.large_neighbourhood_config;
+        check(
+            is_ratio(large_neighbourhood_config.reaction_factor),
+            "large_neighbourhood_config.reaction_factor",
+            "must be between 0 and 1",
+        );
+        check(
+            (1..=48).contains(&large_neighbourhood_config.time_band_width),
+            "large_neighbourhood_config.time_band_width",
+            "must be between 1 and 48",
+        );
+
+        check(
+            self.exact_search_config.node_limit > 0,
+            "exact_search_config.node_limit",
+            "must be at least 1",
+        );
+
+        let multi_objective_config = &self.multi_objective_config;
+        check(
+            !multi_objective_config.objectives.is_empty(),
+            "multi_objective_config.objectives",
+            "must contain at least one penalty type",
+        );
+        check(
+            multi_objective_config.archive_size > 0,
+            "multi_objective_config.archive_size",
+            "must be at least 1",
+        );
+
+        check(
+            self.explanation_config
+                .output_path
+                .as_ref()
+                .is_none_or(|output_path| has_existing_directory(output_path)),
+            "explanation_config.output_path",
+            "must be in an existing directory",
+        );
+
+        let tuning_config = &self.tuning_config;
+        check(
+            is_positive(tuning_config.time_budget_seconds),
+            "tuning_config.time_budget_seconds",
+            "must be a positive number of seconds",
+        );
+        check(
+            tuning_config.candidates > 0,
+            "tuning_config.candidates",
+            "must be at least 1",
+        );
+        check(
+            tuning_config.max_rounds > 0,
+            "tuning_config.max_rounds",
+            "must be at least 1",
+        );
+        check(
+            tuning_config.min_rounds <= tuning_config.max_rounds,
+            "tuning_config.min_rounds",
+            "must not be larger than max_rounds",
+        );
+
+        let (min_temperature, max_temperature) = tuning_config.temperature_range;
+        check(
+            is_positive(min_temperature) && min_temperature <= max_temperature,
+            "tuning_config.temperature_range",
+            "must be positive and ordered",
+        );
+        let (min_cooling_factor, max_cooling_factor) = tuning_config.cooling_factor_range;
+        check(
+            min_cooling_factor > 0.0
+                && min_cooling_factor <= max_cooling_factor
+                && max_cooling_factor < 1.0,
+            "tuning_config.cooling_factor_range",
+            "must be ordered and between 0 and 1 (exclusive)",
+        );
+        let (min_iterations, max_iterations) = tuning_config.iterations_range;
+        check(
+            min_iterations > 0 && min_iterations <= max_iterations,
+            "tuning_config.iterations_range",
+            "must be positive and ordered",
+        );
+        check(
+            (1..=u32::MAX / 3).contains(&tuning_config.max_strategy_chance),
+            "tuning_config.max_strategy_chance",
+            "must be between 1 and 1431655765, so the three drawn chances add up to a u32",
+        );
+        check(
+            has_existing_directory(&tuning_config.output_path),
+            "tuning_config.output_path",
+            "must be in an existing directory",
+        );
+
+        check(
+            self.report_config
+                .output_path
+                .as_ref()
+                .is_none_or(|output_path| has_existing_directory(output_path)),
+            "report_config.output_path",
+            "must be in an existing directory",
+        );
+
+        if let Err(message) = self.filter_config.filter() {
+            check(false, "filter_config", &format!("has an invalid selector: {}", message));
+        }
+
+        let batch_config = &self.batch_config;
+        let mut scenario_names: HashSet<&str> = HashSet::new();
+        let mut scenario_file_names: HashSet<String> =
+            HashSet::from([scenario_file_name(BASELINE_SCENARIO)]);
+        for (index, scenario) in batch_config.scenarios.iter().enumerate() {
+            let field = format!("batch_config.scenarios[{}]", index);
+            check(
+                !scenario.name.trim().is_empty(),
+                &format!("{}.name", field),
+                "must not be empty",
+            );
+            check(
+                scenario_names.insert(&scenario.name),
+                &format!("{}.name", field),
+                "must be unique",
+            );
+            check(
+                scenario.name != BASELINE_SCENARIO,
+                &format!("{}.name", field),
+                "must not be the name of the baseline scenario",
+            );
+            check(
+                scenario_file_names.insert(scenario_file_name(&scenario.name)),
+                &format!("{}.name", field),
+                "must give a schedule file name unlike the other scenarios (only letters, digits, \
+                 dashes and underscores are kept)",
+            );
+            if let Err(message) = scenario.filter.filter() {
+                check(false, &field, &format!("has an invalid selector: {}", message));
+            }
         }
+        check(
+            batch_config
+                .output_path
+                .as_ref()
+                .is_none_or(|output_path| has_existing_directory(output_path)),
+            "batch_config.output_path",
+            "must be in an existing directory",
+        );
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigError::Invalid(errors)),
+        }
+    }
+}
+
+/// Checks whether a number is finite and positive
+fn is_positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+/// Checks whether a number is between 0 and 1
+fn is_ratio(value: f32) -> bool {
+    (0.0..=1.0).contains(&value)
+}
 
-        Ok(())
+/// Checks whether the directory of a file path exists
+fn has_existing_directory(path: &str) -> bool {
+    match Path::new(path).parent() {
+        Some(directory) => directory.as_os_str().is_empty() || directory.is_dir(),
+        None => false,
     }
 }
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -13,12 +14,16 @@ use crate::{
     schedule::{Schedule, Slot},
 };
 
+pub mod authoring;
 pub mod constraint_builder;
+pub mod constraint_file;
+pub mod constraint_filter;
 pub mod constraint_store;
+pub mod merge;
 pub mod penalties;
 pub mod penalty;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum ConstraintPriority {
     High,
     Low,
@@ -38,20 +43,68 @@ pub enum ConstraintPriority {
 /// start slot is specified)
 /// * `preferred_slots` - Optional list of preferred time slots as (day, slot) tuples (Only the
 /// start slot is specified)
+/// * `tags` - Labels used to select the constraint in filters and scenarios (e.g. `sport`)
 /// * `scheduled_slot` - The currently assigned time slot, if scheduled
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Constraint {
     pub name: String,
     pub id: u32,
     pub penalties: Vec<Penalty>,
     pub priority: ConstraintPriority,
+    #[schemars(range(min = 1, max = 48))]
     pub duration: u8,
     pub gap: Option<u16>,
     pub allowed_slots: Option<Vec<Slot>>,
     pub preferred_slots: Option<Vec<Slot>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl Constraint {
+    /// Checks the constraint for values the schema of a constraint file cannot rule out
+    ///
+    /// A constraint must fit in a day, and its allowed and preferred slots must be in the week and
+    /// leave room for the whole constraint before the end of the day
+    ///
+    /// # Returns
+    /// * `Vec<String>` - A description of each problem, empty if the constraint is valid
+    pub fn semantic_issues(&self) -> Vec<String> {
+        let mut issues: Vec<String> = Vec::new();
+        if self.duration == 0 || self.duration > 48 {
+            issues.push(format!(
+                "The duration of {} windows must be between 1 and 48 windows, so that the \
+                 constraint fits in a day",
+                self.duration
+            ));
+        }
+
+        for (kind, slots) in [
+            ("allowed", &self.allowed_slots),
+            ("preferred", &self.preferred_slots),
+        ] {
+            for slot in slots.iter().flatten() {
+                if slot.day >= 7 || slot.window >= 48 {
+                    issues.push(format!(
+                        "The {} slot (day {}, window {}) must have a day under 7 and a window \
+                         under 48",
+                        kind, slot.day, slot.window
+                    ));
+                } else if slot.window as u16 + self.duration as u16 > 48 {
+                    issues.push(format!(
+                        "The {} slot {} leaves no room for the {} windows of the constraint \
+                         before the end of the day",
+                        kind, slot, self.duration
+                    ));
+                }
+            }
+        }
+
+        if self.allowed_slots.as_ref().is_some_and(Vec::is_empty) {
+            issues.push("The allowed slots must not be empty".to_string());
+        }
+        issues
+    }
+
     /// Calculates the total penalty for the constraint
     ///
     /// # Returs
@@ -0,0 +1,575 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+    ops::Range,
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    constraints::{
+        ConstraintPriority,
+        constraint_builder::ConstraintBuilder,
+        constraint_file::{ConstraintDefinition, ConstraintTemplate, merge_tags},
+        constraint_store::ConstraintStore,
+    },
+    schedule::{DAY_NAMES, Slot},
+};
+
+/// The number of minutes in a slot
+const SLOT_MINUTES: u32 = 30;
+/// The number of slots in a day
+const SLOTS_PER_DAY: u8 = 48;
+
+/// An error in a constraint file, along with where in the file it occurred
+///
+/// * `line` - The line of the error, starting at 1
+/// * `column` - The column of the error, starting at 1
+/// * `message` - What is wrong
+#[derive(Debug)]
+pub struct AuthoringError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl AuthoringError {
+    /// Locates an error at a byte offset of the source of the constraint file
+    fn at(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let preceding = &source[..offset.min(source.len())];
+        let line_start = preceding.rfind('\n').map_or(0, |index| index + 1);
+
+        AuthoringError {
+            line: preceding.matches('\n').count() + 1,
+            column: preceding[line_start..].chars().count() + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for AuthoringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line,
+            self.column,
+            self.message.trim_end()
+        )
+    }
+}
+
+impl Error for AuthoringError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthoringContents {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default, rename = "template")]
+    templates: BTreeMap<String, AuthoringTemplate>,
+    #[serde(default, rename = "task")]
+    tasks: Vec<Spanned<AuthoringTask>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthoringTask {
+    name: String,
+    template: Option<Spanned<String>>,
+    priority: Option<ConstraintPriority>,
+    duration: Option<Spanned<String>>,
+    frequency: Option<Spanned<u8>>,
+    gap: Option<Spanned<String>>,
+    allowed: Option<Vec<Spanned<String>>>,
+    preferred: Option<Vec<Spanned<String>>>,
+    tags: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AuthoringTemplate {
+    priority: Option<ConstraintPriority>,
+    duration: Option<Spanned<String>>,
+    frequency: Option<Spanned<u8>>,
+    gap: Option<Spanned<String>>,
+    allowed: Option<Vec<Spanned<String>>>,
+    preferred: Option<Vec<Spanned<String>>>,
+    tags: Option<Vec<String>>,
+}
+
+impl AuthoringTask {
+    /// Splits the task into its name, the template it extends and its own fields
+    fn split(self) -> (String, Option<Spanned<String>>, AuthoringTemplate) {
+        let fields = AuthoringTemplate {
+            priority: self.priority,
+            duration: self.duration,
+            frequency: self.frequency,
+            gap: self.gap,
+            allowed: self.allowed,
+            preferred: self.preferred,
+            tags: self.tags,
+        };
+        (self.name, self.template, fields)
+    }
+}
+
+/// The allowed and the preferred slots of a template
+type TemplateSlots = (Option<Vec<Slot>>, Option<Vec<Slot>>);
+
+/// The slot entries of a template written in the human-friendly format, kept to resolve the
+/// slots of the template again for tasks with a different duration
+///
+/// * `allowed` - The entries of the allowed slots
+/// * `preferred` - The entries of the preferred slots
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TemplateSlotEntries {
+    pub allowed: Option<Vec<String>>,
+    pub preferred: Option<Vec<String>>,
+}
+
+impl TemplateSlotEntries {
+    /// Takes the slot entries written for a template
+    fn of(fields: &AuthoringTemplate) -> Option<Self> {
+        let entries = |entries: &Option<Vec<Spanned<String>>>| {
+            entries.as_ref().map(|entries| {
+                entries
+                    .iter()
+                    .map(|entry| entry.get_ref().clone())
+                    .collect()
+            })
+        };
+        let slot_entries = TemplateSlotEntries {
+            allowed: entries(&fields.allowed),
+            preferred: entries(&fields.preferred),
+        };
+        match slot_entries.allowed.is_some() || slot_entries.preferred.is_some() {
+            true => Some(slot_entries),
+            false => None,
+        }
+    }
+
+    /// Resolves the slot entries into the slots a task of a duration may start in
+    ///
+    /// # Arguments
+    /// * `duration` - The duration of the task
+    ///
+    /// # Returns
+    /// * `TemplateSlots` - The allowed and the preferred slots
+    /// * `String` - Why an entry does not fit the duration
+    pub fn resolve(&self, duration: u8) -> Result<TemplateSlots, String> {
+        let resolve = |entries: &Option<Vec<String>>| -> Result<Option<Vec<Slot>>, String> {
+            let Some(entries) = entries else {
+                return Ok(None);
+            };
+            let mut slots: Vec<Slot> = Vec::new();
+            for entry in entries {
+                let entry_slots = parse_slots(entry, duration)
+                    .map_err(|message| format!("The template slot {:?}: {}", entry, message))?;
+                for slot in entry_slots {
+                    if !slots.contains(&slot) {
+                        slots.push(slot);
+                    }
+                }
+            }
+            Ok(Some(slots))
+        };
+        Ok((resolve(&self.allowed)?, resolve(&self.preferred)?))
+    }
+}
+
+/// A constraint file written in the human-friendly TOML format
+///
+/// Each task is a `[[task]]` table, compiled into constraints through the `ConstraintBuilder`, so
+/// the ids and the penalties of the constraints are derived from the fields that are present:
+///
+/// ```toml
+/// include = ["summer.toml"]
+///
+/// [template.workout]
+/// priority = "High"
+/// duration = "1h30m"
+/// gap = "24h"
+///
+/// [[task]]
+/// name = "gym"
+/// template = "workout"
+/// frequency = 3
+/// allowed = ["Mon-Fri 17:00-21:00", "Sat,Sun"]
+/// preferred = ["Mon 18:00", "Wed 18:00"]
+/// tags = ["sport"]
+/// ```
+///
+/// * Durations and gaps are written in hours and minutes on the half hour (e.g. `2h`, `30m` or
+///   `1h30m`)
+/// * Slots are written as days followed by an optional time. The days are a day name, a range of
+///   day names (e.g. `Mon-Fri`) or a comma separated list of them. The time is either the time the
+///   task starts at (e.g. `09:30`) or a time range the whole task must fit in (e.g. `17:00-21:00`).
+///   Without a time, the task may be anywhere in the day
+/// * A `[template.<name>]` table holds defaults for the tasks naming it in their `template` field.
+///   The fields of a task override those of its template, except for the tags which are added to
+///   the tags of the template. The slots of a template are resolved with the duration of each
+///   task extending it, so a time range holds the whole task even when the task is longer
+/// * The files listed in `include` are merged with the file, relative to the file
+pub struct AuthoringFile {
+    source: String,
+    contents: AuthoringContents,
+}
+
+impl AuthoringFile {
+    /// Parses a constraint file written in the human-friendly TOML format
+    ///
+    /// # Arguments
+    /// * `source` - The contents of the constraint file
+    ///
+    /// # Returns
+    /// * `AuthoringFile` - The parsed file, ready to be built
+    /// * `AuthoringError` - If the file is not valid TOML or has unknown or mistyped fields
+    pub fn parse(source: String) -> Result<AuthoringFile, AuthoringError> {
+        let contents: AuthoringContents = toml::from_str(&source).map_err(|error| {
+            AuthoringError::at(
+                &source,
+                error.span().map_or(0, |span| span.start),
+                error.message(),
+            )
+        })?;
+        Ok(AuthoringFile { source, contents })
+    }
+
+    /// Returns the constraint files the file includes
+    pub fn includes(&self) -> &[String] {
+        &self.contents.include
+    }
+
+    /// Resolves the templates of the file
+    ///
+    /// # Returns
+    /// * `Vec<(String, ConstraintTemplate)>` - The name and the resolved fields of each template
+    /// * `AuthoringError` - The first invalid field of a template, with its line and column
+    pub fn templates(&self) -> Result<Vec<(String, ConstraintTemplate)>, AuthoringError> {
+        self.contents
+            .templates
+            .iter()
+            .map(|(name, template)| {
+                let resolved =
+                    resolve_fields(&self.source, template, &ConstraintTemplate::default())?;
+                Ok((name.clone(), resolved))
+            })
+            .collect()
+    }
+
+    /// Compiles the tasks of the file into a constraint store
+    ///
+    /// # Arguments
+    /// * `templates` - The templates of all merged constraint files, by name
+    ///
+    /// # Returns
+    /// * `ConstraintStore` - The constraints of all tasks, numbered in the order of the tasks
+    /// * `AuthoringError` - The first error in the file, with its line and column
+    pub fn build(
+        self,
+        templates: &HashMap<String, ConstraintTemplate>,
+    ) -> Result<ConstraintStore, AuthoringError> {
+        let source = &self.source;
+        let mut builder = ConstraintBuilder::new();
+        let mut constraint_store = ConstraintStore::new();
+        for task in self.contents.tasks {
+            let task_span = task.span();
+            let (name, template_name, fields) = task.into_inner().split();
+            let template = match &template_name {
+                Some(template_name) => templates.get(template_name.get_ref()).ok_or_else(|| {
+                    AuthoringError::at(
+                        source,
+                        template_name.span().start,
+                        format!(
+                            "Unknown template {:?}, which no constraint file defines",
+                            template_name.get_ref()
+                        ),
+                    )
+                })?,
+                None => &ConstraintTemplate::default(),
+            };
+            let resolved = resolve_fields(source, &fields, template)?;
+
+            let definition = ConstraintDefinition {
+                name,
+                template: None,
+                id: None,
+                penalties: None,
+                priority: resolved.priority,
+                duration: resolved.duration,
+                gap: resolved.gap,
+                allowed_slots: resolved.allowed_slots,
+                preferred_slots: resolved.preferred_slots,
+                frequency: resolved.frequency,
+                tags: resolved.tags,
+            };
+            let mut constraints = definition
+                .build(&mut builder)
+                .map_err(|message| AuthoringError::at(source, task_span.start, message))?;
+            constraint_store.push(&mut constraints);
+        }
+
+        Ok(constraint_store)
+    }
+}
+
+/// Resolves the fields of a task or a template written in the human-friendly format, over the
+/// fields of the template it extends
+///
+/// # Arguments
+/// * `source` - The contents of the constraint file
+/// * `fields` - The fields written for the task or template
+/// * `template` - The resolved fields of the template it extends
+///
+/// # Returns
+/// * `ConstraintTemplate` - The resolved fields
+/// * `AuthoringError` - The first invalid field, with its line and column
+fn resolve_fields(
+    source: &str,
+    fields: &AuthoringTemplate,
+    template: &ConstraintTemplate,
+) -> Result<ConstraintTemplate, AuthoringError> {
+    let located =
+        |span: Range<usize>| move |message| AuthoringError::at(source, span.start, message);
+
+    let duration = match &fields.duration {
+        Some(duration) => Some(
+            parse_duration(duration.get_ref())
+                .and_then(|duration| match duration <= SLOTS_PER_DAY as u16 {
+                    true => Ok(duration as u8),
+                    false => Err("A task must fit in a day".to_string()),
+                })
+                .map_err(located(duration.span()))?,
+        ),
+        None => template.duration,
+    };
+    let frequency = match &fields.frequency {
+        Some(frequency) if *frequency.get_ref() == 0 => {
+            return Err(located(frequency.span())(
+                "The frequency value must be greater than 0".to_string(),
+            ));
+        }
+        Some(frequency) => Some(*frequency.get_ref()),
+        None => template.frequency,
+    };
+    let gap = match &fields.gap {
+        Some(gap) => Some(parse_duration(gap.get_ref()).map_err(located(gap.span()))?),
+        None => template.gap,
+    };
+    // NOTE: The slots of a template are resolved with its duration, so they are resolved again
+    // for a task overriding the duration
+    let (inherited_allowed, inherited_preferred) =
+        match (&fields.duration, duration, &template.slot_entries) {
+            (Some(duration_field), Some(duration), Some(slot_entries))
+                if template.duration != Some(duration) =>
+            {
+                slot_entries
+                    .resolve(duration)
+                    .map_err(located(duration_field.span()))?
+            }
+            _ => (
+                template.allowed_slots.clone(),
+                template.preferred_slots.clone(),
+            ),
+        };
+    let resolve_slots =
+        |entries: &Option<Vec<Spanned<String>>>, inherited: &Option<Vec<Slot>>| match entries {
+            Some(entries) => {
+                let duration = duration.ok_or_else(|| {
+                    AuthoringError::at(
+                        source,
+                        entries.first().map_or(0, |entry| entry.span().start),
+                        "Slots can only be resolved for a task with a duration",
+                    )
+                })?;
+                parse_slot_entries(source, entries, duration).map(Some)
+            }
+            None => Ok(inherited.clone()),
+        };
+
+    Ok(ConstraintTemplate {
+        priority: fields.priority.clone().or(template.priority.clone()),
+        duration,
+        gap,
+        allowed_slots: resolve_slots(&fields.allowed, &inherited_allowed)?,
+        preferred_slots: resolve_slots(&fields.preferred, &inherited_preferred)?,
+        frequency,
+        tags: merge_tags(template.tags.clone(), fields.tags.clone()),
+        slot_entries: TemplateSlotEntries::of(fields),
+    })
+}
+
+/// Parses the slot entries of a task into the slots it may start in
+fn parse_slot_entries(
+    source: &str,
+    entries: &[Spanned<String>],
+    duration: u8,
+) -> Result<Vec<Slot>, AuthoringError> {
+    let mut slots: Vec<Slot> = Vec::new();
+    for entry in entries {
+        let entry_slots = parse_slots(entry.get_ref(), duration)
+            .map_err(|message| AuthoringError::at(source, entry.span().start, message))?;
+        for slot in entry_slots {
+            if !slots.contains(&slot) {
+                slots.push(slot);
+            }
+        }
+    }
+    Ok(slots)
+}
+
+/// Parses a duration written in hours and minutes (e.g. `1h30m`) into a number of slots
+///
+/// # Returns
+/// * `u16` - The number of slots of the duration
+/// * `String` - Why the duration is invalid
+fn parse_duration(text: &str) -> Result<u16, String> {
+    let invalid = || {
+        format!(
+            "Invalid duration {:?}, expected hours and minutes like \"2h\", \"30m\" or \"1h30m\"",
+            text
+        )
+    };
+
+    let mut minutes: u32 = 0;
+    let mut number = String::new();
+    for character in text.chars().filter(|character| !character.is_whitespace()) {
+        match character {
+            '0'..='9' => number.push(character),
+            'h' | 'm' if !number.is_empty() => {
+                let value: u32 = number.parse().map_err(|_| invalid())?;
+                let unit = if character == 'h' { 60 } else { 1 };
+                minutes = value
+                    .checked_mul(unit)
+                    .and_then(|value| minutes.checked_add(value))
+                    .ok_or_else(invalid)?;
+                number.clear();
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    if !number.is_empty() || minutes == 0 {
+        return Err(invalid());
+    }
+    if !minutes.is_multiple_of(SLOT_MINUTES) {
+        return Err(format!(
+            "The duration {:?} is not a multiple of {} minutes",
+            text, SLOT_MINUTES
+        ));
+    }
+    u16::try_from(minutes / SLOT_MINUTES).map_err(|_| invalid())
+}
+
+/// Parses a slot entry (e.g. `Mon-Fri 17:00-21:00`) into the slots a task of a duration may start
+/// in
+///
+/// # Returns
+/// * `Vec<Slot>` - The slots the task may start in
+/// * `String` - Why the entry is invalid
+fn parse_slots(text: &str, duration: u8) -> Result<Vec<Slot>, String> {
+    let mut parts = text.split_whitespace();
+    let days = parse_days(parts.next().unwrap_or_default())?;
+    let time = parts.next();
+    if parts.next().is_some() {
+        return Err(format!(
+            "Invalid slot {:?}, expected days followed by an optional time like \"Mon-Fri 09:30\"",
+            text
+        ));
+    }
+
+    if !(1..=SLOTS_PER_DAY).contains(&duration) {
+        return Err(format!(
+            "A task of {} windows does not fit in a day of {} windows",
+            duration, SLOTS_PER_DAY
+        ));
+    }
+
+    let windows: Range<u8> = match time.map(|time| time.split_once('-')) {
+        None => 0..SLOTS_PER_DAY - duration + 1,
+        Some(None) => {
+            let start = parse_time(time.unwrap_or_default())?;
+            if start + duration > SLOTS_PER_DAY {
+                return Err(format!(
+                    "A task starting at {} does not end on the same day",
+                    time.unwrap_or_default()
+                ));
+            }
+            start..start + 1
+        }
+        Some(Some((start, end))) => {
+            let (start_window, end_window) = (parse_time(start)?, parse_time(end)?);
+            if end_window < start_window + duration {
+                return Err(format!(
+                    "The task does not fit between {} and {}",
+                    start, end
+                ));
+            }
+            start_window..end_window - duration + 1
+        }
+    };
+
+    Ok(days
+        .iter()
+        .flat_map(|day| windows.clone().map(|window| Slot { day: *day, window }))
+        .collect())
+}
+
+/// Parses days written as day names, ranges of day names and comma separated lists of them (e.g.
+/// `Mon-Wed,Sat`) into the indices of the days
+fn parse_days(text: &str) -> Result<Vec<u8>, String> {
+    let mut days: Vec<u8> = Vec::new();
+    for part in text.split(',') {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (parse_day(first)?, parse_day(last)?),
+            None => (parse_day(part)?, parse_day(part)?),
+        };
+        if first > last {
+            return Err(format!("The day range {:?} ends before it starts", part));
+        }
+        for day in first..=last {
+            if !days.contains(&day) {
+                days.push(day);
+            }
+        }
+    }
+    Ok(days)
+}
+
+/// Parses a day name (e.g. `Mon`) into the index of the day
+fn parse_day(text: &str) -> Result<u8, String> {
+    DAY_NAMES
+        .iter()
+        .position(|day_name| day_name.eq_ignore_ascii_case(text))
+        .map(|day| day as u8)
+        .ok_or_else(|| {
+            format!(
+                "Unknown day {:?}, expected one of {}",
+                text,
+                DAY_NAMES.join(", ")
+            )
+        })
+}
+
+/// Parses a time of day on the half hour (e.g. `09:30`) into the index of its window
+fn parse_time(text: &str) -> Result<u8, String> {
+    let invalid = || {
+        format!(
+            "Invalid time {:?}, expected a time on the half hour like \"09:30\"",
+            text
+        )
+    };
+
+    let (hours, minutes) = text.split_once(':').ok_or_else(invalid)?;
+    let hours: u8 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u8 = minutes.parse().map_err(|_| invalid())?;
+    let window = match (hours, minutes) {
+        (0..24, 0) | (24, 0) => hours * 2,
+        (0..24, 30) => hours * 2 + 1,
+        _ => return Err(invalid()),
+    };
+    Ok(window)
+}
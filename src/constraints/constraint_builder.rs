@@ -1,16 +1,19 @@
-use anyhow::{Context, Result, anyhow};
+use std::collections::HashSet;
+
+use anyhow::{Context, Result, anyhow, bail};
 
 use crate::{
     constraints::{Constraint, ConstraintPriority, penalty::Penalty},
     schedule::Slot,
 };
 
-#[allow(dead_code)]
 /// A simple builder class used for building constraints
 /// As part of building the constraint, its relevant penalty function is generated.
 /// The generated penalty function depends on the parameters used when building the constraint.
 pub struct ConstraintBuilder {
     id_counter: u32,
+    reserved_ids: HashSet<u32>,
+    id: Option<u32>,
     constraint_name: Option<String>,
     priority: Option<ConstraintPriority>,
     duration: Option<u8>,
@@ -19,13 +22,15 @@ pub struct ConstraintBuilder {
     preferred_slots: Option<Vec<Slot>>,
     penalties: Vec<Penalty>,
     frequency: Option<u8>,
+    tags: Vec<String>,
 }
 
-#[allow(dead_code)]
 impl ConstraintBuilder {
     pub fn new() -> ConstraintBuilder {
         ConstraintBuilder {
             id_counter: 0,
+            reserved_ids: HashSet::new(),
+            id: None,
             constraint_name: None,
             priority: None,
             duration: None,
@@ -34,6 +39,7 @@ impl ConstraintBuilder {
             preferred_slots: None,
             penalties: Vec::new(),
             frequency: None,
+            tags: Vec::new(),
         }
     }
 
@@ -43,6 +49,24 @@ impl ConstraintBuilder {
         self
     }
 
+    /// Set the id of the constraint, instead of assigning the next free id automatically
+    ///
+    /// An id can only be set for a constraint with a frequency of 1
+    pub fn set_id(&mut self, id: u32) -> &mut Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Reserves the ids of constraints that are built with their ids set, so that they are never
+    /// assigned automatically
+    ///
+    /// # Arguments
+    /// * `ids` - The ids to reserve
+    pub fn reserve_ids(&mut self, ids: impl IntoIterator<Item = u32>) -> &mut Self {
+        self.reserved_ids.extend(ids);
+        self
+    }
+
     /// Set the priority of the constraint
     pub fn set_priority(&mut self, priority: ConstraintPriority) -> &mut Self {
         self.priority = Some(priority);
@@ -106,6 +130,14 @@ impl ConstraintBuilder {
         self
     }
 
+    /// Set the tags used to select the constraint in filters and scenarios
+    ///
+    /// Tags do not change the penalties of the constraint
+    pub fn set_tags(&mut self, tags: Vec<String>) -> &mut Self {
+        self.tags = tags;
+        self
+    }
+
     /// Builds and returns the constraint specified
     /// Clears the content of the builder after the constraint is built
     ///
@@ -118,15 +150,21 @@ impl ConstraintBuilder {
                 "Empty constraint, please ensure criteria is specified for the constraint"
             ));
         }
+        if self.id.is_some() && self.frequency.unwrap_or(1) > 1 {
+            bail!("An id can only be set for a constraint with a frequency of 1");
+        }
         let constraints: Result<Vec<Constraint>> = (0..self.frequency.unwrap_or(1))
             .map(|_| {
-                self.id_counter += 1;
+                let id = match self.id {
+                    Some(id) => id,
+                    None => self.next_free_id(),
+                };
                 Ok(Constraint {
                     name: self
                         .constraint_name
                         .clone()
                         .context("Please ensure the name is set for constraint")?,
-                    id: self.id_counter,
+                    id,
                     penalties: self.penalties.clone(),
                     priority: self
                         .priority
@@ -138,6 +176,7 @@ impl ConstraintBuilder {
                     gap: self.gap,
                     allowed_slots: self.allowed_slots.clone(),
                     preferred_slots: self.preferred_slots.clone(),
+                    tags: self.tags.clone(),
                 })
             })
             .collect();
@@ -146,10 +185,23 @@ impl ConstraintBuilder {
         constraints
     }
 
+    /// Assigns the next id that is not reserved
+    fn next_free_id(&mut self) -> u32 {
+        self.id_counter += 1;
+        while self.reserved_ids.contains(&self.id_counter) {
+            self.id_counter += 1;
+        }
+        self.id_counter
+    }
+
     /// Clears the state of the builder
     ///
     /// Typically called after a constraint has been created using `build`
+    ///
+    /// NOTE: The ids assigned so far and the reserved ids are kept, so that the ids of all built
+    /// constraints stay unique
     pub fn clear(&mut self) {
+        self.id = None;
         self.constraint_name = None;
         self.priority = None;
         self.duration = None;
@@ -158,5 +210,6 @@ impl ConstraintBuilder {
         self.preferred_slots = None;
         self.penalties = Vec::new();
         self.frequency = None;
+        self.tags = Vec::new();
     }
 }
@@ -0,0 +1,353 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    fmt,
+};
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::{
+    constraints::{
+        Constraint, ConstraintPriority, authoring::TemplateSlotEntries,
+        constraint_builder::ConstraintBuilder, constraint_store::ConstraintStore, penalty::Penalty,
+    },
+    schedule::Slot,
+};
+
+/// The longest duration of a template, a full day of windows
+const MAX_TEMPLATE_DURATION: u8 = 48;
+
+/// Named defaults for constraints, which a constraint extends by naming the template in its
+/// `template` field
+///
+/// Every field a constraint sets overrides the field of its template, except for the tags which
+/// are added to the tags of the template
+///
+/// # Fields
+///
+/// * `priority` - Optional importance level of the constraints
+/// * `duration` - Optional length of time required for the constraints (in time units)
+/// * `gap` - Optional minimum gap required between scheduling slots
+/// * `allowed_slots` - Optional whitelist of permitted start slots
+/// * `preferred_slots` - Optional list of preferred start slots
+/// * `frequency` - Optional number of times each constraint needs to be scheduled
+/// * `tags` - Optional tags, added to the tags of the constraints
+/// * `slot_entries` - The slot entries of a template written in the human-friendly format, which
+///   are resolved again for constraints with a different duration than the template
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConstraintTemplate {
+    pub priority: Option<ConstraintPriority>,
+    #[schemars(range(min = 1, max = 48))]
+    pub duration: Option<u8>,
+    pub gap: Option<u16>,
+    pub allowed_slots: Option<Vec<Slot>>,
+    pub preferred_slots: Option<Vec<Slot>>,
+    #[schemars(range(min = 1))]
+    pub frequency: Option<u8>,
+    pub tags: Option<Vec<String>>,
+    #[serde(skip)]
+    pub slot_entries: Option<TemplateSlotEntries>,
+}
+
+/// A constraint as written in a json constraint file
+///
+/// The id and the penalties may be left out, in which case the id is assigned automatically and
+/// the penalties are derived from the fields that are present. A constraint with a frequency is
+/// expanded into that many constraints with the same attributes
+///
+/// # Fields
+///
+/// * `name` - Human-readable name for the constraint
+/// * `template` - Optional name of the template the constraint extends
+/// * `id` - Optional unique identifier for the constraint
+/// * `penalties` - Optional penalties of the constraint, which must match the fields present
+/// * `priority` - Importance level of this constraint in the scheduling system, unless set by the
+///   template
+/// * `duration` - Length of time required for this constraint (in time units), unless set by the
+///   template
+/// * `gap` - Optional minimum gap required between scheduling slots
+/// * `allowed_slots` - Optional whitelist of permitted start slots
+/// * `preferred_slots` - Optional list of preferred start slots
+/// * `frequency` - Optional number of times the constraint needs to be scheduled
+/// * `tags` - Optional labels used to select the constraint in filters and scenarios, along with
+///   the tags of the template
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConstraintDefinition {
+    pub name: String,
+    pub template: Option<String>,
+    pub id: Option<u32>,
+    pub penalties: Option<Vec<Penalty>>,
+    pub priority: Option<ConstraintPriority>,
+    #[schemars(range(min = 1, max = 48))]
+    pub duration: Option<u8>,
+    pub gap: Option<u16>,
+    pub allowed_slots: Option<Vec<Slot>>,
+    pub preferred_slots: Option<Vec<Slot>>,
+    #[schemars(range(min = 1))]
+    pub frequency: Option<u8>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl ConstraintDefinition {
+    /// Fills the fields the constraint leaves out with the fields of its template
+    ///
+    /// The slots of a template written in the human-friendly format are resolved again when the
+    /// constraint sets a different duration than the template
+    ///
+    /// # Arguments
+    /// * `index` - The index of the constraint in the file
+    /// * `templates` - The templates of all merged constraint files, by name
+    ///
+    /// # Returns
+    /// * `ConstraintDefinition` - The constraint with the fields of its template
+    /// * `ConstraintFileError` - If no template has the name, or the slots of the template do not
+    ///   fit the duration of the constraint
+    fn extend_template(
+        self,
+        index: usize,
+        templates: &HashMap<String, ConstraintTemplate>,
+    ) -> Result<ConstraintDefinition, ConstraintFileError> {
+        let Some(template_name) = &self.template else {
+            return Ok(self);
+        };
+        let mut template = templates
+            .get(template_name)
+            .ok_or_else(|| {
+                ConstraintFileError::UnknownTemplate(
+                    index,
+                    self.name.clone(),
+                    template_name.clone(),
+                )
+            })?
+            .clone();
+
+        if let (Some(duration), Some(slot_entries)) = (self.duration, &template.slot_entries)
+            && template.duration != Some(duration)
+        {
+            let (allowed_slots, preferred_slots) =
+                slot_entries.resolve(duration).map_err(|message| {
+                    ConstraintFileError::InvalidConstraint(index, self.name.clone(), message)
+                })?;
+            template.allowed_slots = allowed_slots.or(template.allowed_slots);
+            template.preferred_slots = preferred_slots.or(template.preferred_slots);
+        }
+
+        Ok(ConstraintDefinition {
+            priority: self.priority.or(template.priority),
+            duration: self.duration.or(template.duration),
+            gap: self.gap.or(template.gap),
+            allowed_slots: self.allowed_slots.or(template.allowed_slots),
+            preferred_slots: self.preferred_slots.or(template.preferred_slots),
+            frequency: self.frequency.or(template.frequency),
+            tags: merge_tags(template.tags, self.tags),
+            ..self
+        })
+    }
+
+    /// Builds the constraint through a `ConstraintBuilder`, expanding its frequency
+    ///
+    /// The penalties are derived from the fields that are present, and are not compared to the
+    /// penalties the constraint lists
+    ///
+    /// # Arguments
+    /// * `builder` - The builder assigning the ids of the constraints
+    ///
+    /// # Returns
+    /// * `Vec<Constraint>` - A constraint for each time the constraint needs to be scheduled
+    /// * `String` - Why the constraint could not be built
+    pub fn build(self, builder: &mut ConstraintBuilder) -> Result<Vec<Constraint>, String> {
+        builder.set_name(self.name);
+        if let Some(priority) = self.priority {
+            builder.set_priority(priority);
+        }
+        if let Some(duration) = self.duration {
+            builder.set_duration(duration);
+        }
+        if let Some(id) = self.id {
+            builder.set_id(id);
+        }
+        if let Some(frequency) = self.frequency {
+            builder.set_frequency(frequency)?;
+        }
+        if let Some(gap) = self.gap {
+            builder.set_gap(gap);
+        }
+        if let Some(allowed_slots) = self.allowed_slots {
+            builder.set_allowed_slots(allowed_slots);
+        }
+        if let Some(preferred_slots) = self.preferred_slots {
+            builder.set_preferred_slots(preferred_slots);
+        }
+        if let Some(tags) = self.tags {
+            builder.set_tags(tags);
+        }
+
+        builder.build().map_err(|error| error.to_string())
+    }
+}
+
+/// The contents of a json constraint file
+///
+/// # Fields
+///
+/// * `include` - Optional constraint files merged with this file, relative to this file
+/// * `templates` - Optional templates, by name, which the constraints of all merged files can
+///   extend
+/// * `constraints` - The constraints of the file
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConstraintFile {
+    #[serde(default)]
+    pub include: Vec<String>,
+    #[serde(default)]
+    pub templates: BTreeMap<String, ConstraintTemplate>,
+    #[serde(default)]
+    pub constraints: Vec<ConstraintDefinition>,
+}
+
+#[derive(Debug)]
+pub enum ConstraintFileError {
+    // id
+    DuplicateId(u32),
+    // index of the constraint in the file, name, listed penalties, penalties derived from fields
+    MismatchedPenalties(usize, String, Vec<Penalty>, Vec<Penalty>),
+    // index of the constraint in the file, name, why it could not be built
+    InvalidConstraint(usize, String, String),
+    // index of the constraint in the file, name, name of the template
+    UnknownTemplate(usize, String, String),
+    // name of the template, why it is invalid
+    InvalidTemplate(String, String),
+}
+
+impl fmt::Display for ConstraintFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstraintFileError::DuplicateId(id) => {
+                write!(f, "The id {} is used by more than one constraint", id)
+            }
+            ConstraintFileError::MismatchedPenalties(index, name, listed, derived) => write!(
+                f,
+                "Constraint {} ({}) lists the penalties {:?}, but its fields give the penalties \
+                 {:?}",
+                index, name, listed, derived
+            ),
+            ConstraintFileError::InvalidConstraint(index, name, message) => {
+                write!(f, "Constraint {} ({}) is invalid: {}", index, name, message)
+            }
+            ConstraintFileError::UnknownTemplate(index, name, template) => write!(
+                f,
+                "Constraint {} ({}) extends the template {:?}, which no constraint file defines",
+                index, name, template
+            ),
+            ConstraintFileError::InvalidTemplate(name, message) => {
+                write!(f, "Template {:?} is invalid: {}", name, message)
+            }
+        }
+    }
+}
+
+impl Error for ConstraintFileError {}
+
+impl ConstraintFile {
+    /// Returns the templates the file defines, by name
+    ///
+    /// # Returns
+    /// * `Vec<(String, ConstraintTemplate)>` - The templates of the file
+    /// * `ConstraintFileError` - If the duration of a template does not fit in a day
+    pub fn templates(&self) -> Result<Vec<(String, ConstraintTemplate)>, ConstraintFileError> {
+        for (name, template) in &self.templates {
+            if let Some(duration) = template.duration
+                && !(1..=MAX_TEMPLATE_DURATION).contains(&duration)
+            {
+                return Err(ConstraintFileError::InvalidTemplate(
+                    name.clone(),
+                    format!(
+                        "The duration {} is not between 1 and {}",
+                        duration, MAX_TEMPLATE_DURATION
+                    ),
+                ));
+            }
+        }
+        Ok(self
+            .templates
+            .iter()
+            .map(|(name, template)| (name.clone(), template.clone()))
+            .collect())
+    }
+
+    /// Builds the constraints of the file through the `ConstraintBuilder`
+    ///
+    /// # Arguments
+    /// * `templates` - The templates of all merged constraint files, by name
+    ///
+    /// # Returns
+    /// * `ConstraintStore` - The constraints of the file, with frequencies expanded
+    /// * `ConstraintFileError` - If an id is used twice, a template does not exist, the penalties
+    ///   of a constraint do not match its fields or a constraint is otherwise invalid
+    pub fn build(
+        self,
+        templates: &HashMap<String, ConstraintTemplate>,
+    ) -> Result<ConstraintStore, ConstraintFileError> {
+        let mut explicit_ids: HashSet<u32> = HashSet::new();
+        for id in self
+            .constraints
+            .iter()
+            .filter_map(|definition| definition.id)
+        {
+            if !explicit_ids.insert(id) {
+                return Err(ConstraintFileError::DuplicateId(id));
+            }
+        }
+
+        let mut builder = ConstraintBuilder::new();
+        builder.reserve_ids(explicit_ids);
+        let mut constraint_store = ConstraintStore::new();
+        for (index, definition) in self.constraints.into_iter().enumerate() {
+            let name = definition.name.clone();
+            let definition = definition.extend_template(index, templates)?;
+            let penalties = definition.penalties.clone();
+            let mut constraints = definition.build(&mut builder).map_err(|message| {
+                ConstraintFileError::InvalidConstraint(index, name.clone(), message)
+            })?;
+            if let (Some(listed), Some(constraint)) = (&penalties, constraints.first())
+                && !same_penalties(listed, &constraint.penalties)
+            {
+                return Err(ConstraintFileError::MismatchedPenalties(
+                    index,
+                    name,
+                    listed.clone(),
+                    constraint.penalties.clone(),
+                ));
+            }
+            constraint_store.push(&mut constraints);
+        }
+
+        Ok(constraint_store)
+    }
+}
+
+/// Checks whether two lists contain the same penalties, in any order
+fn same_penalties(penalties: &[Penalty], other_penalties: &[Penalty]) -> bool {
+    penalties.len() == other_penalties.len()
+        && penalties
+            .iter()
+            .all(|penalty| other_penalties.contains(penalty))
+}
+
+/// Merges the tags of a template with the tags of a constraint extending it, keeping the order
+/// they are written in
+pub fn merge_tags(
+    template_tags: Option<Vec<String>>,
+    tags: Option<Vec<String>>,
+) -> Option<Vec<String>> {
+    let (Some(template_tags), Some(tags)) = (&template_tags, &tags) else {
+        return tags.or(template_tags);
+    };
+
+    let mut merged_tags = template_tags.clone();
+    merged_tags.extend(tags.iter().filter(|tag| !template_tags.contains(tag)).cloned());
+    Some(merged_tags)
+}
@@ -0,0 +1,109 @@
+use std::{fmt, str::FromStr};
+
+use crate::constraints::Constraint;
+
+/// Selects constraints by one of their tags, their type (name) or their id
+///
+/// Written as `tag:<tag>`, `type:<name>` or `id:<id>`
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConstraintSelector {
+    Tag(String),
+    Type(String),
+    Id(u32),
+}
+
+impl ConstraintSelector {
+    /// Checks whether the selector selects a constraint
+    pub fn matches(&self, constraint: &Constraint) -> bool {
+        match self {
+            ConstraintSelector::Tag(tag) => constraint.tags.contains(tag),
+            ConstraintSelector::Type(name) => constraint.name == *name,
+            ConstraintSelector::Id(id) => constraint.id == *id,
+        }
+    }
+}
+
+impl FromStr for ConstraintSelector {
+    type Err = String;
+
+    fn from_str(selector: &str) -> Result<Self, Self::Err> {
+        match selector.split_once(':') {
+            Some(("tag", tag)) if !tag.is_empty() => Ok(ConstraintSelector::Tag(tag.to_string())),
+            Some(("type", name)) if !name.is_empty() => {
+                Ok(ConstraintSelector::Type(name.to_string()))
+            }
+            Some(("id", id)) => id
+                .parse()
+                .map(ConstraintSelector::Id)
+                .map_err(|_| format!("the id of {:?} is not a number", selector)),
+            _ => Err(format!(
+                "{:?} is not of the form `tag:<tag>`, `type:<name>` or `id:<id>`",
+                selector
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ConstraintSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstraintSelector::Tag(tag) => write!(f, "tag:{}", tag),
+            ConstraintSelector::Type(name) => write!(f, "type:{}", name),
+            ConstraintSelector::Id(id) => write!(f, "id:{}", id),
+        }
+    }
+}
+
+/// Narrows the constraints to solve
+///
+/// * `include` - The selectors of the constraints to keep. All constraints are kept when empty
+/// * `exclude` - The selectors of the constraints to remove from the kept constraints
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintFilter {
+    pub include: Vec<ConstraintSelector>,
+    pub exclude: Vec<ConstraintSelector>,
+}
+
+impl ConstraintFilter {
+    /// Parses the selectors of a filter
+    ///
+    /// # Arguments
+    /// * `include` - The selectors of the constraints to keep
+    /// * `exclude` - The selectors of the constraints to remove
+    ///
+    /// # Returns
+    /// * `ConstraintFilter` - The filter
+    /// * `String` - Why a selector is invalid
+    pub fn parse(include: &[String], exclude: &[String]) -> Result<Self, String> {
+        let parse_selectors = |selectors: &[String]| {
+            selectors
+                .iter()
+                .map(|selector| selector.parse())
+                .collect::<Result<Vec<ConstraintSelector>, String>>()
+        };
+
+        Ok(ConstraintFilter {
+            include: parse_selectors(include)?,
+            exclude: parse_selectors(exclude)?,
+        })
+    }
+
+    /// Checks whether the filter keeps every constraint
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Checks whether the filter keeps a constraint
+    pub fn keeps(&self, constraint: &Constraint) -> bool {
+        let is_included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|selector| selector.matches(constraint));
+        is_included
+            && !self
+                .exclude
+                .iter()
+                .any(|selector| selector.matches(constraint))
+    }
+}
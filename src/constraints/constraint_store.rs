@@ -1,16 +1,22 @@
 use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
-use std::fs::File;
 
-use crate::{constraints::Constraint, schedule::Schedule};
+use crate::{
+    constraints::{
+        Constraint, constraint_filter::ConstraintFilter, merge::load_constraint_files,
+    },
+    report::report,
+    schedule::Schedule,
+};
 use log::info;
 use rand::prelude::*;
 use rand::rng;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 
-#[derive(Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 /// Structure used for storing arbitrary constraints for optimisation
 pub struct ConstraintStore {
     constraints: Vec<Constraint>,
@@ -20,6 +26,8 @@ pub struct ConstraintStore {
 pub enum ConstraintStoreError {
     EmptyStore,
     SelectionError,
+    InvalidConstraints(Vec<String>),
+    NoConstraints,
 }
 
 impl fmt::Display for ConstraintStoreError {
@@ -31,6 +39,16 @@ impl fmt::Display for ConstraintStoreError {
             ConstraintStoreError::SelectionError => {
                 write!(f, "Error selecting constraint from store")
             }
+            ConstraintStoreError::InvalidConstraints(issues) => {
+                write!(f, "Invalid constraints:")?;
+                for issue in issues {
+                    write!(f, "\n  - {}", issue)?;
+                }
+                Ok(())
+            }
+            ConstraintStoreError::NoConstraints => {
+                write!(f, "The constraint files define no constraints")
+            }
         }
     }
 }
@@ -53,6 +71,11 @@ impl ConstraintStore {
         self.constraints.len()
     }
 
+    /// Checks whether no constraints are stored
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+
     /// Add a new constraint to the store
     ///
     /// # Arguments
@@ -177,6 +200,50 @@ impl ConstraintStore {
             .collect()
     }
 
+    /// Narrows the stored constraints with a filter
+    ///
+    /// The kept constraints keep their ids, so schedules of the narrowed constraints can be
+    /// compared with schedules of all constraints
+    ///
+    /// # Arguments
+    /// * `filter` - The filter selecting the constraints to keep
+    ///
+    /// # Returns
+    /// * `ConstraintStore` - A constraint store with the kept constraints
+    pub fn filter(&self, filter: &ConstraintFilter) -> ConstraintStore {
+        ConstraintStore {
+            constraints: self
+                .constraints
+                .iter()
+                .filter(|c| filter.keeps(c))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Checks every stored constraint for values the schema of a constraint file cannot rule out
+    /// (see `Constraint::semantic_issues`)
+    ///
+    /// # Returns
+    /// * `ConstraintStoreError::InvalidConstraints` - With every problem of every constraint, if
+    ///   any constraint is invalid
+    pub fn check(&self) -> Result<(), ConstraintStoreError> {
+        let issues: Vec<String> = self
+            .constraints
+            .iter()
+            .flat_map(|c| {
+                c.semantic_issues()
+                    .into_iter()
+                    .map(|issue| format!("[{}:{}] {}", c.id, c.name.to_uppercase(), issue))
+            })
+            .collect();
+
+        match issues.is_empty() {
+            true => Ok(()),
+            false => Err(ConstraintStoreError::InvalidConstraints(issues)),
+        }
+    }
+
     /// Exports the constraints to a json file
     ///
     /// # Arguments
@@ -206,31 +273,31 @@ impl ConstraintStore {
         let coverage = (scheduled.len() as f32 / total as f32) * 100.0;
 
         // Log the summary statistics
-        info!("");
-        info!("--- SCHEDULE REPORT SUMMARY ---");
-        info!("Total Constraints:       {}", total);
-        info!("Successfully Scheduled:  {}", scheduled.len());
-        info!("Non-scheduled (Gaps):    {}", non_scheduled.len());
-        info!("Schedule Fulfillment:    {:.1}%", coverage);
-        info!("Total Incurred Penalty:  {}", total_incurred_penalty);
+        report!("");
+        report!("--- SCHEDULE REPORT SUMMARY ---");
+        report!("Total Constraints:       {}", total);
+        report!("Successfully Scheduled:  {}", scheduled.len());
+        report!("Non-scheduled (Gaps):    {}", non_scheduled.len());
+        report!("Schedule Fulfillment:    {:.1}%", coverage);
+        report!("Total Incurred Penalty:  {}", total_incurred_penalty);
 
         // List specific constraints
         if !scheduled.is_empty() {
-            info!("--- SCHEDULED CONSTRAINTS ---");
+            report!("--- SCHEDULED CONSTRAINTS ---");
             for c in scheduled {
-                info!("  [OK] id: {:<3} | type: {}", c.id, c.name);
+                report!("  [OK] id: {:<3} | type: {}", c.id, c.name);
             }
         }
 
         if !non_scheduled.is_empty() {
-            info!("--- NON-SCHEDULED CONSTRAINTS ---");
+            report!("--- NON-SCHEDULED CONSTRAINTS ---");
             for c in non_scheduled {
-                info!("  [FAIL] id: {:<3} | type: {}", c.id, c.name);
+                report!("  [FAIL] id: {:<3} | type: {}", c.id, c.name);
             }
         }
 
-        info!("-------------------------------");
-        info!("");
+        report!("-------------------------------");
+        report!("");
     }
 }
 
@@ -258,23 +325,32 @@ impl fmt::Debug for ConstraintStore {
     }
 }
 
-/// Reads stored constraint store configuration from json file, deserializes the constraints and
-/// returns the store
+/// Reads constraint files and the files they include, builds their constraints and merges them
+/// into a constraint store
+///
+/// Json files are built through the `ConstraintBuilder` (see `ConstraintFile`), so the ids and
+/// penalties of the constraints may be left out. Files with the .toml suffix are compiled from the
+/// human-friendly format instead (see `AuthoringFile`)
 ///
 /// # Arguments
-/// * `file_name` - The name of the json file containing the serialized constraints store (include
-/// the .json suffix)
+/// * `file_names` - The names of the constraint files, including their suffixes. The constraints
+///   of the first file keep their ids, and those of the others are renumbered (see
+///   `load_constraint_files`)
 ///
 /// # Returns
-/// * `ConstraintStore` - The deserialized constraint store
-/// * `io::Error` - If failed to open file with the given name
-/// * `Error` - If cannot deserialize the contents of the json file into a constraint store
-pub fn load_constraint_store_from_file(
-    file_name: String,
+/// * `ConstraintStore` - The merged constraint store
+/// * `MergeError` - If a file cannot be read or built, is included twice, or the files conflict
+/// * `ConstraintStoreError` - If the files define no constraints, or a constraint does not fit in
+///   a day, or has slots outside the week or too late in the day for it
+pub fn load_constraint_store_from_files(
+    file_names: &[String],
 ) -> Result<ConstraintStore, Box<dyn Error>> {
-    info!("Loading constraints from file ({:?})", file_name);
-    let json_reader = File::open(format!("{}", file_name))?;
-    let constraints: ConstraintStore = serde_json::from_reader(json_reader)?;
-    info!("Loaded constraints from file");
+    info!("Loading constraints from files ({:?})", file_names);
+    let constraints = load_constraint_files(file_names)?;
+    if constraints.is_empty() {
+        return Err(ConstraintStoreError::NoConstraints.into());
+    }
+    constraints.check()?;
+    info!("Loaded constraints from files");
     Ok(constraints)
 }
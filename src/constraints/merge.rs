@@ -0,0 +1,303 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt,
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use log::info;
+
+use crate::constraints::{
+    Constraint,
+    authoring::AuthoringFile,
+    constraint_file::{ConstraintFile, ConstraintTemplate},
+    constraint_store::ConstraintStore,
+};
+
+#[derive(Debug)]
+pub enum MergeError {
+    // path of the file
+    IncludedTwice(String),
+    // name of the template, paths of the files defining it differently
+    ConflictingTemplates(String, String, String),
+    // description of each conflict
+    ConflictingConstraints(Vec<String>),
+    // path of the file, the error in the file
+    InFile(String, Box<dyn Error>),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MergeError::IncludedTwice(path) => write!(
+                f,
+                "The constraint file {:?} is included more than once, or includes itself",
+                path
+            ),
+            MergeError::ConflictingTemplates(name, path, other_path) => write!(
+                f,
+                "The template {:?} is defined differently in {:?} and {:?}",
+                name, path, other_path
+            ),
+            MergeError::ConflictingConstraints(conflicts) => {
+                write!(f, "Conflicting constraints:")?;
+                for conflict in conflicts {
+                    write!(f, "\n  - {}", conflict)?;
+                }
+                Ok(())
+            }
+            MergeError::InFile(path, error) => write!(f, "{}: {}", path, error),
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+/// A constraint file in either of the formats constraint files are written in
+enum ConstraintSource {
+    Json(ConstraintFile),
+    Toml(AuthoringFile),
+}
+
+impl ConstraintSource {
+    /// Reads a constraint file, in the human-friendly format if it has the .toml suffix and as
+    /// json otherwise
+    fn read(path: &Path) -> Result<ConstraintSource, Box<dyn Error>> {
+        match path
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            true => Ok(ConstraintSource::Toml(AuthoringFile::parse(
+                fs::read_to_string(path)?,
+            )?)),
+            false => Ok(ConstraintSource::Json(serde_json::from_reader(
+                File::open(path)?,
+            )?)),
+        }
+    }
+
+    /// Returns the constraint files the file includes
+    fn includes(&self) -> Vec<String> {
+        match self {
+            ConstraintSource::Json(constraint_file) => constraint_file.include.clone(),
+            ConstraintSource::Toml(authoring_file) => authoring_file.includes().to_vec(),
+        }
+    }
+
+    /// Returns the templates the file defines, by name
+    fn templates(&self) -> Result<Vec<(String, ConstraintTemplate)>, Box<dyn Error>> {
+        match self {
+            ConstraintSource::Json(constraint_file) => Ok(constraint_file.templates()?),
+            ConstraintSource::Toml(authoring_file) => Ok(authoring_file.templates()?),
+        }
+    }
+
+    /// Builds the constraints of the file
+    fn build(
+        self,
+        templates: &HashMap<String, ConstraintTemplate>,
+    ) -> Result<ConstraintStore, Box<dyn Error>> {
+        match self {
+            ConstraintSource::Json(constraint_file) => Ok(constraint_file.build(templates)?),
+            ConstraintSource::Toml(authoring_file) => Ok(authoring_file.build(templates)?),
+        }
+    }
+}
+
+/// A constraint file read for merging
+///
+/// * `path` - The path of the file, as it was given or included
+/// * `canonical_path` - The canonical path of the file, to detect files included twice
+/// * `source` - The contents of the file
+struct SourceFile {
+    path: String,
+    canonical_path: PathBuf,
+    source: ConstraintSource,
+}
+
+/// Loads constraint files and the files they include, and merges them into one constraint store
+///
+/// The templates of all files can be extended by the constraints of all files. The constraints of
+/// the first file keep their ids, and the constraints of the other files are renumbered after them.
+/// Constraints with the same name are of the same type, so constraints of the same name in
+/// different files must agree on their priority, duration and gap
+///
+/// # Arguments
+/// * `file_names` - The constraint files to merge, in order
+///
+/// # Returns
+/// * `ConstraintStore` - The merged constraints
+/// * `MergeError` - If a file is invalid or included twice, or the files conflict
+pub fn load_constraint_files(file_names: &[String]) -> Result<ConstraintStore, MergeError> {
+    let mut source_files: Vec<SourceFile> = Vec::new();
+    for file_name in file_names {
+        read_source_file(Path::new(file_name), &mut source_files)?;
+    }
+
+    let templates = collect_templates(&source_files)?;
+    let mut constraint_stores: Vec<(String, ConstraintStore)> = Vec::new();
+    for source_file in source_files {
+        let constraint_store = source_file
+            .source
+            .build(&templates)
+            .map_err(|error| MergeError::InFile(source_file.path.clone(), error))?;
+        constraint_stores.push((source_file.path, constraint_store));
+    }
+
+    merge_constraint_stores(constraint_stores)
+}
+
+/// Reads a constraint file, followed by the files it includes (relative to the file)
+fn read_source_file(path: &Path, source_files: &mut Vec<SourceFile>) -> Result<(), MergeError> {
+    let path_name = path.display().to_string();
+    let canonical_path = fs::canonicalize(path)
+        .map_err(|error| MergeError::InFile(path_name.clone(), error.into()))?;
+    if source_files
+        .iter()
+        .any(|source_file| source_file.canonical_path == canonical_path)
+    {
+        return Err(MergeError::IncludedTwice(path_name));
+    }
+
+    let source = ConstraintSource::read(path)
+        .map_err(|error| MergeError::InFile(path_name.clone(), error))?;
+    let includes = source.includes();
+    source_files.push(SourceFile {
+        path: path_name,
+        canonical_path,
+        source,
+    });
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    for include in includes {
+        read_source_file(&directory.join(include), source_files)?;
+    }
+    Ok(())
+}
+
+/// Collects the templates of all constraint files by name
+///
+/// # Returns
+/// * `HashMap<String, ConstraintTemplate>` - The templates by name
+/// * `MergeError` - If a template is invalid, or two files define a template differently
+fn collect_templates(
+    source_files: &[SourceFile],
+) -> Result<HashMap<String, ConstraintTemplate>, MergeError> {
+    let mut templates: HashMap<String, (ConstraintTemplate, &str)> = HashMap::new();
+    for source_file in source_files {
+        let file_templates = source_file
+            .source
+            .templates()
+            .map_err(|error| MergeError::InFile(source_file.path.clone(), error))?;
+
+        for (name, template) in file_templates {
+            match templates.get(&name) {
+                Some((defined_template, _)) if *defined_template == template => {}
+                Some((_, defined_path)) => {
+                    return Err(MergeError::ConflictingTemplates(
+                        name,
+                        defined_path.to_string(),
+                        source_file.path.clone(),
+                    ));
+                }
+                None => {
+                    templates.insert(name, (template, &source_file.path));
+                }
+            }
+        }
+    }
+
+    Ok(templates
+        .into_iter()
+        .map(|(name, (template, _))| (name, template))
+        .collect())
+}
+
+/// Merges the constraint stores of constraint files, renumbering the constraints of all but the
+/// first file after the constraints before them
+///
+/// # Arguments
+/// * `constraint_stores` - The path of each constraint file and its constraints, in order
+///
+/// # Returns
+/// * `ConstraintStore` - The merged constraints
+/// * `MergeError` - If constraints of the same name in different files disagree on their
+///   priority, duration or gap
+fn merge_constraint_stores(
+    constraint_stores: Vec<(String, ConstraintStore)>,
+) -> Result<ConstraintStore, MergeError> {
+    let mut merged_store = ConstraintStore::new();
+    let mut types: HashMap<String, (&str, Constraint)> = HashMap::new();
+    let mut reported: HashSet<(String, &str)> = HashSet::new();
+    let mut conflicts: Vec<String> = Vec::new();
+    let mut max_id: u32 = 0;
+
+    for (index, (path, constraint_store)) in constraint_stores.iter().enumerate() {
+        let mut constraints: Vec<Constraint> = constraint_store.into_iter().cloned().collect();
+        if index > 0 && !constraints.is_empty() {
+            info!(
+                "Renumbering the constraints of {:?} from id {}",
+                path,
+                max_id + 1
+            );
+            for constraint in constraints.iter_mut() {
+                max_id += 1;
+                constraint.id = max_id;
+            }
+        }
+        max_id = constraints
+            .iter()
+            .map(|constraint| constraint.id)
+            .fold(max_id, u32::max);
+
+        for constraint in &constraints {
+            match types.get(&constraint.name) {
+                Some((type_path, type_constraint))
+                    if type_path != path
+                        && !is_same_type(type_constraint, constraint)
+                        && reported.insert((constraint.name.clone(), path)) =>
+                {
+                    conflicts.push(format!(
+                        "{:?} has {} in {:?}, but {} in {:?}",
+                        constraint.name,
+                        describe_type(type_constraint),
+                        type_path,
+                        describe_type(constraint),
+                        path
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    types.insert(constraint.name.clone(), (path, constraint.clone()));
+                }
+            }
+        }
+        merged_store.push(&mut constraints);
+    }
+
+    match conflicts.is_empty() {
+        true => Ok(merged_store),
+        false => Err(MergeError::ConflictingConstraints(conflicts)),
+    }
+}
+
+/// Checks whether two constraints of the same name agree on the attributes shared by their type
+fn is_same_type(constraint: &Constraint, other_constraint: &Constraint) -> bool {
+    constraint.priority == other_constraint.priority
+        && constraint.duration == other_constraint.duration
+        && constraint.gap == other_constraint.gap
+}
+
+/// Describes the attributes shared by the constraints of a type
+fn describe_type(constraint: &Constraint) -> String {
+    format!(
+        "priority {:?}, duration {} and {}",
+        constraint.priority,
+        constraint.duration,
+        match constraint.gap {
+            Some(gap) => format!("gap {}", gap),
+            None => "no gap".to_string(),
+        }
+    )
+}
@@ -1,10 +1,9 @@
 use std::collections::HashMap;
 
-use log::info;
-
 use crate::{
     config::PenaltiesConfig,
     constraints::{Constraint, constraint_store::ConstraintStore, penalty::Penalty},
+    report::report,
     schedule::{Schedule, Slot},
 };
 
@@ -240,7 +239,7 @@ pub fn calculate_gap_based_penalty(
 ///
 /// # Returns
 /// * `u16` - The total number of slots between the two slots
-fn calculate_gap_between_slots(slot_one: &Slot, slot_two: &Slot) -> u16 {
+pub fn calculate_gap_between_slots(slot_one: &Slot, slot_two: &Slot) -> u16 {
     let mut day_gap = slot_one.day as i16 - slot_two.day as i16;
 
     if day_gap.abs() > 3 {
@@ -274,9 +273,9 @@ pub fn print_penalty_report(
     let penalties = calculate_detailed_penalties(constraints, schedule, penalties_config);
 
     // Header - Minimalist and wide-screen friendly
-    info!("=== PENALTY REPORT SUMMARY ===");
-    info!("TOTAL INCURRED PENALTY: {}", total_incurred_penalty);
-    info!("---");
+    report!("=== PENALTY REPORT SUMMARY ===");
+    report!("TOTAL INCURRED PENALTY: {}", total_incurred_penalty);
+    report!("---");
 
     let mut collected: Vec<_> = penalties.iter().collect();
 
@@ -293,7 +292,7 @@ pub fn print_penalty_report(
             continue;
         }
 
-        info!(
+        report!(
             "[{}:{}] Total: {}",
             id,
             constraint_name.to_uppercase(),
@@ -302,11 +301,11 @@ pub fn print_penalty_report(
 
         for (reason, value) in instances {
             // This format string is flexible; no vertical bars to misalign
-            info!("  └─ {}: {}", reason, value);
+            report!("  └─ {}: {}", reason, value);
         }
 
-        info!("");
+        report!("");
     }
 
-    info!("==============================");
+    report!("==============================");
 }
@@ -0,0 +1,486 @@
+use std::{mem, ops::Range};
+
+use log::{debug, info};
+
+use crate::{
+    alternatives::AlternativeArchive,
+    config::{Config, PenaltiesConfig},
+    constraints::{
+        Constraint, ConstraintPriority,
+        constraint_store::ConstraintStore,
+        penalties::{calculate_gap_between_slots, calculate_penalties},
+        penalty::Penalty,
+    },
+    global_search::HillClimbResult,
+    large_neighbourhood_search::recreate::{candidate_slots, slot_cost_lower_bound},
+    schedule::{Schedule, Slot},
+    stats::{ExactSearchStats, OptimisationStats},
+    stop_condition::{StopCondition, StopReason},
+};
+
+/// Runs a branch and bound search over every placement of every constraint (including leaving it
+/// unscheduled) to find the schedule with the lowest penalty
+///
+/// Constraints are assigned one at a time, the most constrained first, trying the placements that
+/// increase the penalty the least first. A partial assignment is pruned when its penalty plus a
+/// lower bound on the penalty change of the remaining constraints can not improve on the best
+/// schedule found so far. The lower bound schedules each remaining constraint in its best free
+/// slot (including its gap penalty against the constraints assigned so far), as scheduling a
+/// constraint can never decrease the penalty of another constraint
+///
+/// Interchangeable constraints (e.g. several identical study sessions) form a group. Within a
+/// group, the bound gives each constraint a different slot, and the constraints are assigned in
+/// order of their slots so that the same schedule is not explored once per permutation
+///
+/// If the search completes, the best schedule is a proven optimum. Otherwise (node limit, time
+/// limit or interrupt) the best schedule found is returned together with a lower bound on the
+/// optimal penalty
+///
+/// # Arguments
+/// * `constraints` - The constraints store containing all the constraints to satisfy
+/// * `config` - The configuration of the search
+/// * `stop_condition` - The wall-clock/interrupt based condition of the search
+/// * `incumbent` - A schedule (e.g. the result of an annealing run) to start the search from. The
+///   search then either improves on it or proves it optimal
+///
+/// # Returns
+/// * (Schedule, u32, OptimisationStats) - The best schedule found, its penalty and the statistics
+///   of the search
+pub fn run_exact_search(
+    constraints: &ConstraintStore,
+    config: &Config,
+    stop_condition: &StopCondition,
+    incumbent: Option<HillClimbResult>,
+) -> HillClimbResult {
+    let mut search = BranchAndBound::new(
+        constraints,
+        &config.penalties_config,
+        stop_condition,
+        config.exact_search_config.node_limit,
+    );
+    let empty_total_penalty = search.best_cost;
+    let mut stats = OptimisationStats {
+        alternative_archive: AlternativeArchive::new(&config.alternatives_config),
+        ..OptimisationStats::default()
+    };
+    if let Some((schedule, total_penalty, incumbent_stats)) = incumbent {
+        search.best_schedule = schedule;
+        search.best_cost = total_penalty as i64;
+        stats = incumbent_stats;
+    }
+    search.alternative_archive = mem::take(&mut stats.alternative_archive);
+
+    info!(
+        "Running exact search. Initial penalty: {:?}",
+        search.best_cost
+    );
+    let root_lower_bound = search.branch(&mut Schedule::new(), empty_total_penalty);
+
+    let lower_bound = root_lower_bound.clamp(0, search.best_cost) as u32;
+    let best_total_penalty = search.best_cost as u32;
+    let proven_optimal = lower_bound == best_total_penalty;
+    if proven_optimal {
+        info!("Exact search complete. Schedule is proven optimal");
+    } else {
+        info!(
+            "Exact search stopped after {:?} nodes. Lower bound on the optimal penalty: {:?}",
+            search.node_count, lower_bound
+        );
+    }
+
+    if search.stop_reason.is_some() {
+        stats.stop_reason = search.stop_reason;
+    }
+    stats.exact_search = Some(ExactSearchStats {
+        node_count: search.node_count,
+        best_total_penalty,
+        lower_bound,
+        proven_optimal,
+    });
+    stats.alternative_archive = search.alternative_archive;
+    (search.best_schedule, best_total_penalty, stats)
+}
+
+/// Calculates a lower bound on the total penalty of every schedule of the constraints
+///
+/// The bound is the bound of the exact search before any constraint is assigned
+///
+/// # Arguments
+/// * `constraints` - The constraints store containing all the constraints to schedule
+/// * `penalties_config` - The configuration specifying values used for penalties
+pub fn calculate_lower_bound(
+    constraints: &ConstraintStore,
+    penalties_config: &PenaltiesConfig,
+) -> u32 {
+    let stop_condition = StopCondition::new(None);
+    let search = BranchAndBound::new(constraints, penalties_config, &stop_condition, 0);
+    (search.best_cost + search.remaining_lower_bound(&Schedule::new())).max(0) as u32
+}
+
+/// Checks whether swapping the placements of two constraints can never change the penalty of a
+/// schedule
+fn is_interchangeable(first: &Constraint, second: &Constraint) -> bool {
+    first.name == second.name
+        && first.penalties == second.penalties
+        && first.priority == second.priority
+        && first.duration == second.duration
+        && first.gap == second.gap
+        && first.allowed_slots == second.allowed_slots
+        && first.preferred_slots == second.preferred_slots
+}
+
+/// Checks whether a constraint scheduled in a slot is too close to a constraint of the same type
+/// scheduled in another slot
+fn violates_gap(constraint: &Constraint, slot: &Slot, other_slot: &Slot) -> bool {
+    constraint
+        .gap
+        .is_some_and(|gap| calculate_gap_between_slots(other_slot, slot) < gap)
+}
+
+/// State shared by the nodes of the branch and bound search
+struct BranchAndBound<'a> {
+    constraints: &'a ConstraintStore,
+    penalties_config: &'a PenaltiesConfig,
+    order: Vec<&'a Constraint>,
+    // The ranges (in the assignment order) of interchangeable constraints
+    groups: Vec<Range<usize>>,
+    // placement_costs[i] - Every slot of constraint i with the change in penalty (apart from gap
+    // penalties) from scheduling it there, in slot order
+    placement_costs: Vec<Vec<(Slot, i64)>>,
+    // slot_costs[i] - The slots decreasing the penalty of constraint i when scheduled on their own,
+    // with the (negative) change in penalty, best first
+    slot_costs: Vec<Vec<(Slot, i64)>>,
+    // remaining_lower_bounds[i] - A lower bound on the change in penalty from assigning the
+    // constraints i.. on any schedule
+    remaining_lower_bounds: Vec<i64>,
+    stop_condition: &'a StopCondition,
+    node_limit: u64,
+    node_count: u64,
+    stopped: bool,
+    stop_reason: Option<StopReason>,
+    // The placement of each assigned constraint, in the assignment order
+    assignment: Vec<Option<Slot>>,
+    best_schedule: Schedule,
+    best_cost: i64,
+    // Every improving schedule found is offered to the archive
+    alternative_archive: AlternativeArchive,
+}
+
+impl<'a> BranchAndBound<'a> {
+    /// Prepares the search, with the empty schedule as the best schedule found so far
+    ///
+    /// # Arguments
+    /// * `constraints` - The constraints store containing all the constraints to satisfy
+    /// * `penalties_config` - The configuration specifying values used for penalties
+    /// * `stop_condition` - The wall-clock/interrupt based condition of the search
+    /// * `node_limit` - The maximum number of nodes to explore
+    fn new(
+        constraints: &'a ConstraintStore,
+        penalties_config: &'a PenaltiesConfig,
+        stop_condition: &'a StopCondition,
+        node_limit: u64,
+    ) -> Self {
+        let mut order: Vec<&Constraint> = constraints.into_iter().collect();
+        order.sort_by(|first, second| {
+            let key = |constraint: &Constraint| {
+                (
+                    constraint
+                        .allowed_slots
+                        .as_ref()
+                        .map_or(usize::MAX, |allowed_slots| allowed_slots.len()),
+                    constraint.priority != ConstraintPriority::High,
+                    std::cmp::Reverse(constraint.duration),
+                )
+            };
+            key(first)
+                .cmp(&key(second))
+                .then_with(|| first.name.cmp(&second.name))
+                .then_with(|| first.id.cmp(&second.id))
+        });
+
+        let mut groups: Vec<Range<usize>> = Vec::new();
+        for (i, constraint) in order.iter().enumerate() {
+            match groups.last_mut() {
+                Some(group) if is_interchangeable(order[group.start], constraint) => {
+                    group.end = i + 1
+                }
+                _ => groups.push(i..i + 1),
+            }
+        }
+
+        // NOTE: The gap penalties are left out of these costs, as they depend on the other
+        // scheduled constraints
+        let mut empty_schedule = Schedule::new();
+        let placement_costs: Vec<Vec<(Slot, i64)>> = order
+            .iter()
+            .map(|constraint| {
+                candidate_slots(&empty_schedule, constraint.duration)
+                    .into_iter()
+                    .map(|slot| {
+                        let cost = slot_cost_lower_bound(
+                            &mut empty_schedule,
+                            constraints,
+                            constraint,
+                            &slot,
+                            penalties_config,
+                        );
+                        (slot, cost)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // NOTE: Only the slots that decrease the penalty compared to leaving the constraint
+        // unscheduled can lower the bound, so the rest are not kept
+        let slot_costs: Vec<Vec<(Slot, i64)>> = placement_costs
+            .iter()
+            .map(|placement_costs| {
+                let mut slot_costs: Vec<(Slot, i64)> = placement_costs
+                    .iter()
+                    .filter(|(_, cost)| *cost < 0)
+                    .cloned()
+                    .collect();
+                slot_costs.sort_by_key(|(_, cost)| *cost);
+                slot_costs
+            })
+            .collect();
+
+        let mut remaining_lower_bounds = vec![0; order.len() + 1];
+        for i in (0..order.len()).rev() {
+            remaining_lower_bounds[i] = remaining_lower_bounds[i + 1]
+                + slot_costs[i].first().map_or(0, |(_, cost)| *cost);
+        }
+
+        let (_, empty_total_penalty) =
+            calculate_penalties(constraints, &empty_schedule, penalties_config);
+
+        BranchAndBound {
+            constraints,
+            penalties_config,
+            order,
+            groups,
+            placement_costs,
+            slot_costs,
+            remaining_lower_bounds,
+            stop_condition,
+            node_limit,
+            node_count: 0,
+            stopped: false,
+            stop_reason: None,
+            assignment: Vec::new(),
+            best_schedule: empty_schedule,
+            best_cost: empty_total_penalty as i64,
+            alternative_archive: AlternativeArchive::default(),
+        }
+    }
+}
+
+impl BranchAndBound<'_> {
+    /// Explores the assignments of the unassigned constraints
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule with the assigned constraints scheduled
+    /// * `cost` - The total penalty of the schedule, counting the unassigned constraints as
+    ///   unscheduled
+    ///
+    /// # Returns
+    /// * `i64` - A lower bound on the penalty of every complete assignment below this node
+    fn branch(&mut self, schedule: &mut Schedule, cost: i64) -> i64 {
+        let index = self.assignment.len();
+        let bound = cost + self.remaining_lower_bound(schedule);
+        if bound >= self.best_cost || self.should_stop() {
+            return bound;
+        }
+        self.node_count += 1;
+
+        if index == self.order.len() {
+            debug!("Exact search found schedule with penalty {:?}", cost);
+            self.best_cost = cost;
+            self.best_schedule = schedule.clone();
+            self.alternative_archive.offer(schedule, cost as u32);
+            return cost;
+        }
+
+        // NOTE: An interchangeable constraint assigned right before this one must be placed in an
+        // earlier slot, or left unscheduled only if this one is left unscheduled as well
+        let constraint = self.order[index];
+        let previous_placement = match self.groups.iter().find(|group| group.contains(&index)) {
+            Some(group) if group.start < index => Some(self.assignment[index - 1].clone()),
+            _ => None,
+        };
+
+        let mut children: Vec<(Option<Slot>, i64)> = Vec::new();
+        if previous_placement != Some(None) {
+            let scheduled_same_type = self.scheduled_same_type(schedule, constraint);
+            let gap_penalty = self.gap_penalty(constraint);
+
+            for (slot, placement_cost) in &self.placement_costs[index] {
+                if let Some(Some(previous_slot)) = &previous_placement
+                    && (slot.day, slot.window) <= (previous_slot.day, previous_slot.window)
+                {
+                    continue;
+                }
+
+                if !schedule.is_duration_free(slot, constraint.duration) {
+                    continue;
+                }
+
+                // NOTE: Scheduling the constraint can add its own gap penalty, and the gap penalty
+                // of every constraint of the same type that did not incur it yet
+                let mut delta = *placement_cost;
+                if scheduled_same_type
+                    .iter()
+                    .any(|(_, other_slot, _)| violates_gap(constraint, slot, other_slot))
+                {
+                    delta += gap_penalty;
+                }
+                for (other, other_slot, other_incurs_gap_penalty) in &scheduled_same_type {
+                    if !other_incurs_gap_penalty && violates_gap(other, other_slot, slot) {
+                        delta += self.gap_penalty(other);
+                    }
+                }
+                children.push((Some(slot.clone()), delta));
+            }
+        }
+        // NOTE: Leaving the constraint unscheduled is tried last out of the children with the same
+        // change in penalty
+        children.push((None, 0));
+        children.sort_by_key(|(_, delta)| *delta);
+
+        let mut subtree_bound = i64::MAX;
+        for (slot, delta) in children {
+            // NOTE: The children are sorted by this (cheaper, static) bound, so once a child is
+            // pruned (or the search is stopped) the bound of every remaining child is at least as
+            // large
+            let child_bound = cost + delta + self.remaining_lower_bounds[index + 1];
+            if child_bound >= self.best_cost || self.should_stop() {
+                subtree_bound = subtree_bound.min(child_bound);
+                break;
+            }
+
+            if let Some(slot) = &slot {
+                schedule.schedule_constraint(constraint.id, constraint.duration, slot);
+            }
+            self.assignment.push(slot);
+            let child_subtree_bound = self.branch(schedule, cost + delta);
+            if self.assignment.pop().flatten().is_some() {
+                schedule
+                    .unschedule_constraint(constraint.id)
+                    .expect("LOGIC ERROR: Could not unschedule scheduled constraint");
+            }
+            subtree_bound = subtree_bound.min(child_subtree_bound);
+        }
+
+        subtree_bound
+    }
+
+    /// Calculates a lower bound on the change in the total penalty caused by assigning the
+    /// unassigned constraints, given the constraints assigned so far
+    ///
+    /// Each unassigned constraint is either left unscheduled or scheduled in a slot that is still
+    /// free, where it incurs its own penalties and a gap penalty if a constraint of the same type
+    /// is already scheduled too close. Interchangeable constraints are scheduled in different slots
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule with the assigned constraints scheduled
+    fn remaining_lower_bound(&self, schedule: &Schedule) -> i64 {
+        let index = self.assignment.len();
+        let mut lower_bound = 0;
+
+        for group in self.groups.iter().filter(|group| group.end > index) {
+            let unassigned_count = group.end - group.start.max(index);
+            let constraint = self.order[group.start];
+            let gap_penalty = self.gap_penalty(constraint);
+            let scheduled_same_type = self.scheduled_same_type(schedule, constraint);
+
+            let mut costs: Vec<i64> = self.slot_costs[group.start]
+                .iter()
+                .filter(|(slot, _)| schedule.is_duration_free(slot, constraint.duration))
+                .map(|(slot, slot_cost)| {
+                    if scheduled_same_type
+                        .iter()
+                        .any(|(_, other_slot, _)| violates_gap(constraint, slot, other_slot))
+                    {
+                        slot_cost + gap_penalty
+                    } else {
+                        *slot_cost
+                    }
+                })
+                .filter(|cost| *cost < 0)
+                .collect();
+            costs.sort_unstable();
+            lower_bound += costs.iter().take(unassigned_count).sum::<i64>();
+        }
+
+        lower_bound
+    }
+
+    /// Returns the gap penalty of a constraint, or 0 if it does not have a gap penalty
+    fn gap_penalty(&self, constraint: &Constraint) -> i64 {
+        if !constraint.penalties.contains(&Penalty::Gap) {
+            return 0;
+        }
+
+        match constraint.priority {
+            ConstraintPriority::High => self.penalties_config.gap_high as i64,
+            ConstraintPriority::Low => self.penalties_config.gap_low as i64,
+        }
+    }
+
+    /// Returns the scheduled constraints of the same type as the specified constraint
+    ///
+    /// # Returns
+    /// * `Vec<(&Constraint, &Slot, bool)>` - Each scheduled constraint of the same type, its slot
+    ///   and whether it currently incurs its gap penalty
+    fn scheduled_same_type<'s>(
+        &'s self,
+        schedule: &'s Schedule,
+        constraint: &Constraint,
+    ) -> Vec<(&'s Constraint, &'s Slot, bool)> {
+        let scheduled: Vec<(&Constraint, &Slot)> = self
+            .constraints
+            .get_constraint_ids_of_type(&constraint.name)
+            .into_iter()
+            .filter(|constraint_id| *constraint_id != constraint.id)
+            .filter_map(|constraint_id| {
+                Some((
+                    self.constraints.get_constraint(constraint_id)?,
+                    schedule.get_scheduled_slot_for_constraint(constraint_id)?,
+                ))
+            })
+            .collect();
+
+        scheduled
+            .iter()
+            .map(|(other, other_slot)| {
+                let incurs_gap_penalty = self.gap_penalty(other) > 0
+                    && scheduled.iter().any(|(another, another_slot)| {
+                        another.id != other.id && violates_gap(other, other_slot, another_slot)
+                    });
+                (*other, *other_slot, incurs_gap_penalty)
+            })
+            .collect()
+    }
+
+    /// Checks whether the node limit is reached or the stop condition is met
+    ///
+    /// NOTE: The stop condition is only checked every 1024 nodes
+    fn should_stop(&mut self) -> bool {
+        if self.stopped {
+            return true;
+        }
+
+        if self.node_count >= self.node_limit {
+            info!("Exact search reached the node limit");
+            self.stopped = true;
+        } else if self.node_count.is_multiple_of(1024)
+            && let Some(stop_reason) = self.stop_condition.check()
+        {
+            info!("Stopping exact search ({})", stop_reason);
+            self.stop_reason = Some(stop_reason);
+            self.stopped = true;
+        }
+
+        self.stopped
+    }
+}
@@ -0,0 +1,438 @@
+use std::{collections::HashSet, error::Error};
+
+use log::info;
+use serde::Serialize;
+
+use crate::{
+    config::PenaltiesConfig,
+    constraints::{
+        Constraint,
+        constraint_store::ConstraintStore,
+        penalties::{calculate_detailed_penalties, calculate_gap_between_slots},
+        penalty::Penalty,
+    },
+    report::report,
+    schedule::{Schedule, Slot},
+};
+
+/// The kind of slot a constraint asks to be scheduled in
+#[derive(Clone, Copy, Debug, Serialize)]
+pub enum SlotKind {
+    Allowed,
+    Preferred,
+}
+
+/// An allowed or preferred slot of a constraint that it could not be scheduled in
+///
+/// * `kind` - Whether the slot is an allowed or a preferred slot
+/// * `slot` - The start slot
+/// * `fits_in_day` - Whether the constraint ends before the end of the day when started in the
+///   slot
+/// * `blocking_constraint_ids` - The constraints occupying the slots the constraint would need
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockedSlot {
+    pub kind: SlotKind,
+    pub slot: Slot,
+    pub fits_in_day: bool,
+    pub blocking_constraint_ids: Vec<u32>,
+}
+
+/// A scheduled constraint of the same type that is closer than the required gap
+///
+/// * `constraint_id` - The id of the conflicting constraint
+/// * `slot` - The slot the conflicting constraint is scheduled in
+/// * `gap` - The number of slots between the two constraints
+/// * `required_gap` - The minimum number of slots required between the two constraints
+#[derive(Clone, Debug, Serialize)]
+pub struct GapConflict {
+    pub constraint_id: u32,
+    pub slot: Slot,
+    pub gap: u16,
+    pub required_gap: u16,
+}
+
+/// A change to the schedule that places a constraint in a different slot
+///
+/// * `slot` - The slot to schedule the constraint in
+/// * `unscheduled_constraint_ids` - The constraints that have to be unscheduled to free the slot
+/// * `penalty_delta` - The change in the total penalty of the schedule (negative if the change
+///   improves the schedule)
+#[derive(Clone, Debug, Serialize)]
+pub struct SuggestedChange {
+    pub slot: Slot,
+    pub unscheduled_constraint_ids: Vec<u32>,
+    pub penalty_delta: i64,
+}
+
+/// The reasons a constraint is unscheduled or incurs a penalty in a schedule
+///
+/// * `constraint_id` - The id of the constraint
+/// * `name` - The name of the constraint
+/// * `scheduled_slot` - The slot the constraint is scheduled in, if scheduled
+/// * `incurred_penalties` - The penalties incurred by the constraint, by type
+/// * `blocked_slots` - The allowed and preferred slots the constraint could not be scheduled in
+/// * `gap_conflicts` - The constraints of the same type closer than the required gap
+/// * `cheapest_change` - The change placing the constraint in a penalty free slot that increases
+///   the total penalty the least
+#[derive(Clone, Debug, Serialize)]
+pub struct ConstraintExplanation {
+    pub constraint_id: u32,
+    pub name: String,
+    pub scheduled_slot: Option<Slot>,
+    pub incurred_penalties: Vec<(Penalty, u32)>,
+    pub blocked_slots: Vec<BlockedSlot>,
+    pub gap_conflicts: Vec<GapConflict>,
+    pub cheapest_change: Option<SuggestedChange>,
+}
+
+impl ConstraintExplanation {
+    /// Returns the total penalty incurred by the constraint
+    pub fn total_penalty(&self) -> u32 {
+        self.incurred_penalties.iter().map(|(_, value)| value).sum()
+    }
+}
+
+/// Explains why each of the unscheduled or penalised constraints of a schedule is not in a
+/// penalty free slot
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing all constraints
+/// * `schedule` - The schedule to explain
+/// * `penalties_config` - The configuration specifying values used for penalties
+///
+/// # Returns
+/// * `Vec<ConstraintExplanation>` - The explanations, ordered by descending incurred penalty
+pub fn explain_schedule(
+    constraints: &ConstraintStore,
+    schedule: &Schedule,
+    penalties_config: &PenaltiesConfig,
+) -> Vec<ConstraintExplanation> {
+    let penalties = calculate_detailed_penalties(constraints, schedule, penalties_config);
+
+    let mut explanations: Vec<ConstraintExplanation> = constraints
+        .into_iter()
+        .filter_map(|constraint| {
+            let incurred_penalties: Vec<(Penalty, u32)> = penalties
+                .get(&constraint.id)
+                .into_iter()
+                .flatten()
+                .filter(|(_, value)| *value > 0)
+                .cloned()
+                .collect();
+            if incurred_penalties.is_empty() && schedule.is_constraint_scheduled(constraint.id) {
+                return None;
+            }
+
+            Some(ConstraintExplanation {
+                constraint_id: constraint.id,
+                name: constraint.name.clone(),
+                scheduled_slot: schedule
+                    .get_scheduled_slot_for_constraint(constraint.id)
+                    .cloned(),
+                incurred_penalties,
+                blocked_slots: find_blocked_slots(constraint, schedule),
+                gap_conflicts: find_gap_conflicts(constraint, schedule, constraints),
+                cheapest_change: find_cheapest_change(
+                    constraint,
+                    schedule,
+                    constraints,
+                    penalties_config,
+                ),
+            })
+        })
+        .collect();
+
+    explanations.sort_by_key(|explanation| {
+        (
+            std::cmp::Reverse(explanation.total_penalty()),
+            explanation.constraint_id,
+        )
+    });
+    explanations
+}
+
+/// Finds the allowed and preferred slots of a constraint that it is not scheduled in, along with
+/// the constraints occupying them
+///
+/// Allowed slots are only listed when the constraint is not scheduled in one of them, and
+/// likewise for preferred slots
+fn find_blocked_slots(constraint: &Constraint, schedule: &Schedule) -> Vec<BlockedSlot> {
+    let scheduled_slot = schedule.get_scheduled_slot_for_constraint(constraint.id);
+    let mut blocked_slots = Vec::new();
+
+    for (kind, slots) in [
+        (SlotKind::Allowed, &constraint.allowed_slots),
+        (SlotKind::Preferred, &constraint.preferred_slots),
+    ] {
+        let Some(slots) = slots else {
+            continue;
+        };
+        if scheduled_slot.is_some_and(|scheduled_slot| slots.contains(scheduled_slot)) {
+            continue;
+        }
+
+        for slot in slots {
+            let fits_in_day = slot.day < 7 && slot.window as u16 + constraint.duration as u16 <= 48;
+            let blocking_constraint_ids = match fits_in_day {
+                true => occupying_constraint_ids(schedule, constraint, slot),
+                false => Vec::new(),
+            };
+            if fits_in_day && blocking_constraint_ids.is_empty() {
+                continue;
+            }
+
+            blocked_slots.push(BlockedSlot {
+                kind,
+                slot: slot.clone(),
+                fits_in_day,
+                blocking_constraint_ids,
+            });
+        }
+    }
+
+    blocked_slots
+}
+
+/// Finds the scheduled constraints of the same type that are closer to a constraint than its
+/// required gap
+fn find_gap_conflicts(
+    constraint: &Constraint,
+    schedule: &Schedule,
+    constraints: &ConstraintStore,
+) -> Vec<GapConflict> {
+    let (Some(required_gap), Some(scheduled_slot)) = (
+        constraint.gap,
+        schedule.get_scheduled_slot_for_constraint(constraint.id),
+    ) else {
+        return Vec::new();
+    };
+    if !constraint.penalties.contains(&Penalty::Gap) {
+        return Vec::new();
+    }
+
+    constraints
+        .get_constraint_ids_of_type(&constraint.name)
+        .into_iter()
+        .filter(|constraint_id| *constraint_id != constraint.id)
+        .filter_map(|constraint_id| {
+            let slot = schedule.get_scheduled_slot_for_constraint(constraint_id)?;
+            let gap = calculate_gap_between_slots(slot, scheduled_slot);
+            (gap < required_gap).then(|| GapConflict {
+                constraint_id,
+                slot: slot.clone(),
+                gap,
+                required_gap,
+            })
+        })
+        .collect()
+}
+
+/// Finds the change that moves a constraint into a slot where it incurs no penalty, with the
+/// lowest penalty delta for the whole schedule
+///
+/// Every start slot of the week is tried, unscheduling the constraints occupying it. Ties are
+/// broken in favour of the change unscheduling fewer constraints
+fn find_cheapest_change(
+    constraint: &Constraint,
+    schedule: &Schedule,
+    constraints: &ConstraintStore,
+    penalties_config: &PenaltiesConfig,
+) -> Option<SuggestedChange> {
+    let scheduled_slot = schedule.get_scheduled_slot_for_constraint(constraint.id);
+    let mut cheapest_change: Option<SuggestedChange> = None;
+
+    for day in 0..7 {
+        for window in 0..=48u8.checked_sub(constraint.duration)? {
+            let slot = Slot { day, window };
+            if scheduled_slot == Some(&slot) {
+                continue;
+            }
+
+            let unscheduled_constraint_ids = occupying_constraint_ids(schedule, constraint, &slot);
+            let mut changed_schedule = schedule.clone();
+            let _ = changed_schedule.unschedule_constraint(constraint.id);
+            for constraint_id in &unscheduled_constraint_ids {
+                let _ = changed_schedule.unschedule_constraint(*constraint_id);
+            }
+            changed_schedule.schedule_constraint(constraint.id, constraint.duration, &slot);
+            if constraint.calculate_penalty(&changed_schedule, constraints, penalties_config) > 0 {
+                continue;
+            }
+
+            let penalty_delta = calculate_penalty_delta(
+                constraint,
+                &unscheduled_constraint_ids,
+                schedule,
+                &changed_schedule,
+                constraints,
+                penalties_config,
+            );
+            let is_cheaper = cheapest_change.as_ref().is_none_or(|cheapest_change| {
+                (penalty_delta, unscheduled_constraint_ids.len())
+                    < (
+                        cheapest_change.penalty_delta,
+                        cheapest_change.unscheduled_constraint_ids.len(),
+                    )
+            });
+            if is_cheaper {
+                cheapest_change = Some(SuggestedChange {
+                    slot,
+                    unscheduled_constraint_ids,
+                    penalty_delta,
+                });
+            }
+        }
+    }
+
+    cheapest_change
+}
+
+/// Calculates the change in the total penalty of a schedule caused by moving a constraint and
+/// unscheduling the constraints in its way
+///
+/// Only the penalties of the changed constraints and the constraints sharing a type with them
+/// can change, so only those are evaluated
+fn calculate_penalty_delta(
+    constraint: &Constraint,
+    unscheduled_constraint_ids: &[u32],
+    schedule: &Schedule,
+    changed_schedule: &Schedule,
+    constraints: &ConstraintStore,
+    penalties_config: &PenaltiesConfig,
+) -> i64 {
+    let mut affected_types: HashSet<&String> = HashSet::from([&constraint.name]);
+    affected_types.extend(
+        unscheduled_constraint_ids
+            .iter()
+            .filter_map(|constraint_id| constraints.get_constraint(*constraint_id))
+            .map(|unscheduled_constraint| &unscheduled_constraint.name),
+    );
+
+    constraints
+        .into_iter()
+        .filter(|affected_constraint| affected_types.contains(&affected_constraint.name))
+        .map(|affected_constraint| {
+            affected_constraint.calculate_penalty(changed_schedule, constraints, penalties_config)
+                as i64
+                - affected_constraint.calculate_penalty(schedule, constraints, penalties_config)
+                    as i64
+        })
+        .sum()
+}
+
+/// Returns the ids of the constraints, other than the specified constraint, occupying the slots
+/// the constraint would need if started in the specified slot
+fn occupying_constraint_ids(schedule: &Schedule, constraint: &Constraint, slot: &Slot) -> Vec<u32> {
+    let mut constraint_ids: Vec<u32> = (slot.window..slot.window + constraint.duration)
+        .filter_map(|window| schedule.grid[slot.day as usize][window as usize])
+        .filter(|constraint_id| *constraint_id != constraint.id)
+        .collect();
+    constraint_ids.dedup();
+    constraint_ids
+}
+
+/// Writes the explanations of the unscheduled and penalised constraints to the report output
+///
+/// # Arguments
+/// * `explanations` - The explanations to report
+/// * `constraints` - The constraint store containing all constraints
+pub fn print_explanation_report(
+    explanations: &[ConstraintExplanation],
+    constraints: &ConstraintStore,
+) {
+    let describe = |constraint_ids: &[u32]| -> String {
+        constraint_ids
+            .iter()
+            .map(
+                |constraint_id| match constraints.get_constraint(*constraint_id) {
+                    Some(constraint) => {
+                        format!("{}:{}", constraint_id, constraint.name.to_uppercase())
+                    }
+                    None => constraint_id.to_string(),
+                },
+            )
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+
+    report!("=== EXPLANATION REPORT ===");
+    if explanations.is_empty() {
+        report!("Every constraint is scheduled without a penalty");
+    }
+
+    for explanation in explanations {
+        let placement = match &explanation.scheduled_slot {
+            Some(slot) => format!("scheduled {}", slot),
+            None => "not scheduled".to_string(),
+        };
+        report!(
+            "[{}:{}] Total: {} ({})",
+            explanation.constraint_id,
+            explanation.name.to_uppercase(),
+            explanation.total_penalty(),
+            placement
+        );
+
+        for (penalty, value) in &explanation.incurred_penalties {
+            report!("  └─ {}: {}", penalty, value);
+        }
+
+        for blocked_slot in &explanation.blocked_slots {
+            let reason = match blocked_slot.fits_in_day {
+                true => format!(
+                    "blocked by {}",
+                    describe(&blocked_slot.blocking_constraint_ids)
+                ),
+                false => "does not fit before the end of the day".to_string(),
+            };
+            report!(
+                "  └─ {:?} slot {} {}",
+                blocked_slot.kind, &blocked_slot.slot, reason
+            );
+        }
+
+        for gap_conflict in &explanation.gap_conflicts {
+            report!(
+                "  └─ Gap of {} slots (at least {} required) to {} at {}",
+                gap_conflict.gap,
+                gap_conflict.required_gap,
+                describe(&[gap_conflict.constraint_id]),
+                &gap_conflict.slot
+            );
+        }
+
+        match &explanation.cheapest_change {
+            Some(change) if change.unscheduled_constraint_ids.is_empty() => report!(
+                "  └─ Cheapest change: move to {} (penalty {:+})",
+                &change.slot, change.penalty_delta
+            ),
+            Some(change) => report!(
+                "  └─ Cheapest change: move to {} unscheduling {} (penalty {:+})",
+                &change.slot,
+                describe(&change.unscheduled_constraint_ids),
+                change.penalty_delta
+            ),
+            None => report!("  └─ No slot in the week is free of penalties for the constraint"),
+        }
+
+        report!("");
+    }
+
+    report!("==========================");
+}
+
+/// Exports the explanations to a json file
+///
+/// # Arguments
+/// * `explanations` - The explanations to export
+/// * `file_name` - The path of the json file to export to
+pub fn export_explanations(
+    explanations: &[ConstraintExplanation],
+    file_name: &str,
+) -> Result<(), Box<dyn Error>> {
+    info!("Exporting explanations to json ({:?})", file_name);
+    let json_string = serde_json::to_string_pretty(explanations)?;
+    std::fs::write(file_name, json_string)?;
+    info!("Exported explanations to json");
+    Ok(())
+}
@@ -0,0 +1,184 @@
+mod crossover;
+
+use std::error::Error;
+
+use crossover::crossover;
+use log::{debug, info};
+use rand::{random, rng, seq::IndexedRandom};
+use rayon::prelude::*;
+
+use crate::{
+    alternatives::AlternativeArchive,
+    config::Config,
+    constraints::{constraint_store::ConstraintStore, penalties::calculate_penalties},
+    hill_climber::{
+        make_small_change::evolve_schedule, run_hill_climber, strategy_selection::StrategySelector,
+    },
+    schedule::Schedule,
+    stats::OptimisationStats,
+    stop_condition::StopCondition,
+};
+
+/// Runs a genetic algorithm to generate a schedule to satisfy specified constraints
+///
+/// The population is seeded with quasi random schedules. Every generation, the `elitism` best
+/// schedules are carried over unchanged, and the rest of the next generation is bred from parents
+/// chosen by tournament selection. Children are combined by crossover and mutated using the
+/// optimisation strategies of the hill climber. In hybrid mode (`hybrid_annealing_iterations`
+/// greater than 0) every child is then refined by a short annealing run
+///
+/// # Arguments
+/// * `constraints` - The constraints store containing all the constraints to satisfy
+/// * `config` - The configuration of the search
+/// * `stop_condition` - The wall-clock/interrupt based condition checked before every generation
+///
+/// # Returns
+/// * (Schedule, u32, OptimisationStats) - The best schedule found, its penalty and the statistics
+///   of the run
+pub fn run_genetic_algorithm(
+    constraints: &ConstraintStore,
+    config: &Config,
+    stop_condition: &StopCondition,
+) -> Result<(Schedule, u32, OptimisationStats), Box<dyn Error>> {
+    let genetic_config = &config.genetic_config;
+    let refinement_config = Config {
+        iterations: genetic_config.hybrid_annealing_iterations,
+        ..config.clone()
+    };
+    let mut stats = OptimisationStats {
+        alternative_archive: AlternativeArchive::new(&config.alternatives_config),
+        ..OptimisationStats::default()
+    };
+
+    let mut population: Vec<(Schedule, u32)> = (0..genetic_config.population_size)
+        .into_par_iter()
+        .map(|i| {
+            let schedule = Schedule::random(constraints, config.random_seed, Some(i));
+            let (_, total_penalty) =
+                calculate_penalties(constraints, &schedule, &config.penalties_config);
+            (schedule, total_penalty)
+        })
+        .collect();
+    population.sort_by_key(|(_, total_penalty)| *total_penalty);
+
+    for generation in 0..genetic_config.generations {
+        if population[0].1 == 0 {
+            break;
+        }
+
+        if let Some(stop_reason) = stop_condition.check() {
+            info!("Stopping at generation {:?} ({})", generation, stop_reason);
+            stats.stop_reason = Some(stop_reason);
+            break;
+        }
+
+        debug!(
+            "Running generation {:?}. Best penalty: {:?}",
+            generation, population[0].1
+        );
+        stats.generation_count += 1;
+
+        let children: Vec<Schedule> = (genetic_config.elitism..genetic_config.population_size)
+            .map(|_| breed(constraints, config, &population, &mut stats))
+            .collect::<Result<_, _>>()?;
+
+        let evaluated_children: Vec<(Schedule, u32, OptimisationStats)> = children
+            .into_par_iter()
+            .map(|child| {
+                if genetic_config.hybrid_annealing_iterations == 0 {
+                    let (_, total_penalty) =
+                        calculate_penalties(constraints, &child, &config.penalties_config);
+                    return Ok((child, total_penalty, OptimisationStats::default()));
+                }
+
+                run_hill_climber(constraints, child, &refinement_config, stop_condition)
+                    .map_err(|error| error.to_string())
+            })
+            .collect::<Result<_, String>>()?;
+
+        population.truncate(genetic_config.elitism as usize);
+        for (child, total_penalty, refinement_stats) in evaluated_children {
+            stats.merge(&refinement_stats);
+            population.push((child, total_penalty));
+        }
+        population.sort_by_key(|(_, total_penalty)| *total_penalty);
+    }
+
+    for (schedule, total_penalty) in &population {
+        stats.alternative_archive.offer(schedule, *total_penalty);
+    }
+
+    let (best_schedule, best_total_penalty) = population.swap_remove(0);
+    info!("Genetic algorithm complete. Identified best schedule");
+    Ok((best_schedule, best_total_penalty, stats))
+}
+
+/// Breeds a single child from two parents chosen by tournament selection
+///
+/// # Arguments
+/// * `constraints` - The constraints store containing all the constraints to satisfy
+/// * `config` - The configuration of the search
+/// * `population` - The current population with the penalty of each schedule
+/// * `stats` - The statistics of the run
+///
+/// # Returns
+/// * `Schedule` - The bred child
+fn breed(
+    constraints: &ConstraintStore,
+    config: &Config,
+    population: &[(Schedule, u32)],
+    stats: &mut OptimisationStats,
+) -> Result<Schedule, Box<dyn Error>> {
+    let genetic_config = &config.genetic_config;
+    let first_parent = tournament_selection(population, genetic_config.tournament_size);
+
+    let mut child = if random::<f32>() < genetic_config.crossover_rate {
+        stats.crossover_count += 1;
+        let second_parent = tournament_selection(population, genetic_config.tournament_size);
+        crossover(
+            constraints,
+            first_parent,
+            second_parent,
+            genetic_config.crossover_operator,
+        )
+    } else {
+        first_parent.clone()
+    };
+
+    if random::<f32>() < genetic_config.mutation_rate {
+        let (penalties, total_penalty) =
+            calculate_penalties(constraints, &child, &config.penalties_config);
+
+        // NOTE: Constraints are chosen for mutation weighted by their penalties, so a schedule
+        // without any penalty cannot be mutated
+        if total_penalty > 0 {
+            evolve_schedule(
+                constraints,
+                &penalties,
+                &mut child,
+                stats,
+                // NOTE: A mutated child is only evaluated with the rest of its generation, so the
+                // strategies are not rewarded and keep their configured chances
+                &mut StrategySelector::new(&config.optimisation_strategy_config),
+            )?;
+        }
+    }
+
+    Ok(child)
+}
+
+/// Chooses a schedule from the population using tournament selection
+///
+/// # Arguments
+/// * `population` - The current population with the penalty of each schedule
+/// * `tournament_size` - The number of randomly chosen schedules competing in the tournament
+///
+/// # Returns
+/// * `&Schedule` - The schedule with the lowest penalty out of the competing schedules
+fn tournament_selection(population: &[(Schedule, u32)], tournament_size: u32) -> &Schedule {
+    let (schedule, _) = population
+        .choose_multiple(&mut rng(), tournament_size as usize)
+        .min_by_key(|(_, total_penalty)| *total_penalty)
+        .expect("LOGIC ERROR: Tournament selection from an empty population");
+    schedule
+}
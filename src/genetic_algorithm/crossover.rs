@@ -0,0 +1,133 @@
+use std::array;
+
+use rand::{random, rng, seq::SliceRandom};
+
+use crate::{
+    config::CrossoverOperator,
+    constraints::{Constraint, constraint_store::ConstraintStore},
+    hill_climber::make_small_change::SchedulableSlots,
+    schedule::Schedule,
+};
+
+/// Combines two parent schedules into a child schedule
+///
+/// After combining, the child is repaired: constraints scheduled by either parent but left
+/// unscheduled in the child (because their placement overlapped another placement) are scheduled
+/// where a parent placed them if that is free, or otherwise in a free compatible slot
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints to schedule
+/// * `first_parent` - The first parent schedule
+/// * `second_parent` - The second parent schedule
+/// * `crossover_operator` - The crossover used for combining the parents
+///
+/// # Returns
+/// * `Schedule` - The child schedule
+pub fn crossover(
+    constraints: &ConstraintStore,
+    first_parent: &Schedule,
+    second_parent: &Schedule,
+    crossover_operator: CrossoverOperator,
+) -> Schedule {
+    let mut child = Schedule::new();
+    let mut constraint_order: Vec<&Constraint> = constraints.into_iter().collect();
+    constraint_order.shuffle(&mut rng());
+
+    match crossover_operator {
+        CrossoverOperator::DayBlocks => {
+            let from_first_parent: [bool; 7] = array::from_fn(|_| random());
+            for constraint in &constraint_order {
+                for (parent, is_first_parent) in [(first_parent, true), (second_parent, false)] {
+                    let Some(slot) = parent.get_scheduled_slot_for_constraint(constraint.id) else {
+                        continue;
+                    };
+
+                    if from_first_parent[slot.day as usize] == is_first_parent {
+                        try_schedule(&mut child, constraint, parent);
+                    }
+                }
+            }
+        }
+        CrossoverOperator::ConstraintPlacements => {
+            for constraint in &constraint_order {
+                let parents = if random() {
+                    [first_parent, second_parent]
+                } else {
+                    [second_parent, first_parent]
+                };
+
+                if !try_schedule(&mut child, constraint, parents[0]) {
+                    try_schedule(&mut child, constraint, parents[1]);
+                }
+            }
+        }
+    }
+
+    repair(&constraint_order, first_parent, second_parent, &mut child);
+    child
+}
+
+/// Schedules a constraint in the child where the parent placed it, if that is possible
+///
+/// # Arguments
+/// * `child` - The child schedule
+/// * `constraint` - The constraint to schedule
+/// * `parent` - The parent to copy the placement from
+///
+/// # Returns
+/// * `true` - If the constraint was scheduled
+/// * `false` - If the parent did not schedule the constraint, the constraint is already scheduled
+///   in the child or the placement overlaps another constraint in the child
+fn try_schedule(child: &mut Schedule, constraint: &Constraint, parent: &Schedule) -> bool {
+    let Some(slot) = parent.get_scheduled_slot_for_constraint(constraint.id) else {
+        return false;
+    };
+
+    if child.is_constraint_scheduled(constraint.id)
+        || !child.is_duration_free(slot, constraint.duration)
+    {
+        return false;
+    }
+
+    child.schedule_constraint(constraint.id, constraint.duration, slot);
+    true
+}
+
+/// Schedules the constraints that either parent scheduled but the child did not
+///
+/// # Arguments
+/// * `constraint_order` - The constraints to repair, in the order they should be repaired
+/// * `first_parent` - The first parent schedule
+/// * `second_parent` - The second parent schedule
+/// * `child` - The child schedule to repair
+fn repair(
+    constraint_order: &[&Constraint],
+    first_parent: &Schedule,
+    second_parent: &Schedule,
+    child: &mut Schedule,
+) {
+    for constraint in constraint_order {
+        if child.is_constraint_scheduled(constraint.id)
+            || !(first_parent.is_constraint_scheduled(constraint.id)
+                || second_parent.is_constraint_scheduled(constraint.id))
+        {
+            continue;
+        }
+
+        if try_schedule(child, constraint, first_parent)
+            || try_schedule(child, constraint, second_parent)
+        {
+            continue;
+        }
+
+        if let Some(slot) = child.get_free_slot_for_constraint(
+            constraint.duration,
+            &SchedulableSlots {
+                allowed_slots: constraint.allowed_slots.clone(),
+                preferred_slots: constraint.preferred_slots.clone(),
+            },
+        ) {
+            child.schedule_constraint(constraint.id, constraint.duration, &slot);
+        }
+    }
+}
@@ -1,11 +1,24 @@
+mod islands;
+mod parallel_tempering;
+
 use core::fmt;
+use std::error::Error;
 
 use log::{error, info};
 use rayon::prelude::*;
 
 use crate::{
-    config::{OptimisationStrategyConfig, PenaltiesConfig}, constraints::constraint_store::ConstraintStore,
-    hill_climber::run_hill_climber, schedule::Schedule, stats::OptimisationStats,
+    config::{Config, SearchMode},
+    constraints::constraint_store::ConstraintStore,
+    exact_search::run_exact_search,
+    genetic_algorithm::run_genetic_algorithm,
+    global_search::{islands::run_islands, parallel_tempering::run_parallel_tempering},
+    hill_climber::run_hill_climber,
+    large_neighbourhood_search::run_large_neighbourhood_search,
+    multi_objective::run_multi_objective_search,
+    schedule::Schedule,
+    stats::OptimisationStats,
+    stop_condition::StopCondition,
 };
 
 #[derive(Debug)]
@@ -23,27 +36,32 @@ impl fmt::Display for GlobalSearchError {
     }
 }
 
-type HillClimbResult = (Schedule, u32, OptimisationStats);
+impl Error for GlobalSearchError {}
+
+pub type HillClimbResult = (Schedule, u32, OptimisationStats);
 
 /// Runs a global search algorithm to find the optimum schedule for the specified constraints
 ///
-/// Runs a specified number of local searches in parallel and returns the best result
-/// Each local search starts with an initial schedule that is seeded using quasi randomly
+/// The algorithm used is specified by the `search_mode` of the configuration
+/// * `IndependentRuns` - See `run_independent_searches`
+/// * `ParallelTempering` - See `run_parallel_tempering`
+/// * `Islands` - See `run_islands`
+/// * `Genetic` - See `run_genetic_algorithm`
+/// * `LargeNeighbourhood` - See `run_large_neighbourhood_search`
+/// * `Exact` - See `run_exact_search`. When polishing, the independent annealing runs are run first
+///   and the exact search starts from their best schedule
+/// * `MultiObjective` - See `run_multi_objective_search`
 ///
-/// Each local search is a simulated annealing algorithm with restarts upon reaching local minima
+/// Every run stops early when its time limit is reached or the user interrupts the search (Ctrl-C),
+/// in which case the best schedule found so far is still returned
+///
+/// When alternatives are requested, the diverse good schedules found by the search are kept in the
+/// alternative archive of the returned statistics (see `AlternativeArchive::alternatives`)
 ///
 /// # Arguments
-/// * `constraints` (&mut ConstraintStore) - The constraint store containing the constraints to
-/// schedule
-/// * `iterations` (u32) - The number of iteration to run each local search for
-/// * `initial_temperature` (f32) - The starting temperature of each local search
-/// * `cooling_factore` (f32) - The cooling temperature for each local search
-/// * `number_of_parallel_searches` (u32) - The number of parallel local searches to run
-/// * `random_seed` (u32) - The random seed used for the quasi random generator
-/// * `penalties_config (&PenaltiesConfig)` - The configuration specifying values used for penalties
-///     during optimisation
-/// * `optimisation_strategy_config (OptimisationStrategyConfig)` - The configuration speciifying
-///     chances used in choosing optimisation strategy
+/// * `constraints` (&ConstraintStore) - The constraint store containing the constraints to
+///   schedule
+/// * `config` (&Config) - The configuration of the search
 ///
 /// # Returns
 /// * (Schedule, u32, OptimisationStats) - The idenfied best (schedule, its penalty,
@@ -51,28 +69,82 @@ type HillClimbResult = (Schedule, u32, OptimisationStats);
 /// * GlobalSearchError - Error if it was not possible to successfully complete a single local
 /// search
 pub fn run_global_search(
-    constraints: &mut ConstraintStore,
-    iterations: u32,
-    initial_temperature: f32,
-    cooling_factor: f32,
-    number_of_parallel_searches: u32,
-    random_seed: u32,
-    penalties_config: &PenaltiesConfig,
-    optimisation_strategy_config: &OptimisationStrategyConfig
+    constraints: &ConstraintStore,
+    config: &Config,
 ) -> Result<HillClimbResult, GlobalSearchError> {
-    (0..number_of_parallel_searches)
+    let stop_condition = StopCondition::new(config.time_limit_config.global_search_limit());
+
+    let (schedule, total_penalty, mut stats) = match config.search_mode {
+        SearchMode::IndependentRuns => {
+            run_independent_searches(constraints, config, &stop_condition)
+        }
+        SearchMode::ParallelTempering => {
+            run_parallel_tempering(constraints, config, &stop_condition)
+        }
+        SearchMode::Islands => run_islands(constraints, config, &stop_condition),
+        SearchMode::Genetic => run_genetic_algorithm(constraints, config, &stop_condition)
+            .map_err(|error| {
+                error!("Error occurred executing genetic algorithm: {}", error);
+                GlobalSearchError::NoResults
+            }),
+        SearchMode::LargeNeighbourhood => {
+            run_large_neighbourhood_search(constraints, config, &stop_condition)
+        }
+        SearchMode::Exact => {
+            let exact_search_config = &config.exact_search_config;
+            let incumbent = if exact_search_config.polish_annealing_result {
+                Some(run_independent_searches(
+                    constraints,
+                    config,
+                    &stop_condition,
+                )?)
+            } else {
+                None
+            };
+
+            Ok(run_exact_search(
+                constraints,
+                config,
+                &stop_condition.for_run(exact_search_config.time_limit()),
+                incumbent,
+            ))
+        }
+        SearchMode::MultiObjective => {
+            run_multi_objective_search(constraints, config, &stop_condition)
+        }
+    }?;
+
+    // NOTE: The best schedule is offered last so that it is kept by the archive even if the
+    // archive holds another schedule with the same penalty close to it
+    stats.alternative_archive.offer(&schedule, total_penalty);
+    Ok((schedule, total_penalty, stats))
+}
+
+/// Runs `number_of_global_searches` local searches in parallel and returns the best result
+/// Each local search starts with an initial schedule that is seeded using quasi randomly
+///
+/// Each local search is a simulated annealing algorithm with restarts upon reaching local minima
+///
+/// # Arguments
+/// * `constraints` (&ConstraintStore) - The constraint store containing the constraints to
+///   schedule
+/// * `config` (&Config) - The configuration of the search
+/// * `stop_condition` (&StopCondition) - The stop condition of the whole global search
+fn run_independent_searches(
+    constraints: &ConstraintStore,
+    config: &Config,
+    stop_condition: &StopCondition,
+) -> Result<HillClimbResult, GlobalSearchError> {
+    let results: Vec<HillClimbResult> = (0..config.number_of_global_searches)
         .into_par_iter()
         .inspect(|_| info!("Running global search"))
         .filter_map(|i| {
-            let initial_schedule = Schedule::random(constraints, random_seed, Some(i));
+            let initial_schedule = Schedule::random(constraints, config.random_seed, Some(i));
             run_hill_climber(
-                &mut constraints.clone(),
+                constraints,
                 initial_schedule,
-                iterations,
-                initial_temperature,
-                cooling_factor,
-                penalties_config,
-                optimisation_strategy_config
+                config,
+                &stop_condition.for_run(config.time_limit_config.run_limit()),
             )
             .inspect_err(|error| {
                 info!(
@@ -83,7 +155,39 @@ pub fn run_global_search(
             })
             .ok()
         })
-        .min_by_key(|(_, penalty, _)| *penalty)
+        .collect();
+
+    select_best_result(results)
         .inspect(|_| info!("Global search complete. Identified best schedule"))
-        .ok_or(GlobalSearchError::NoResults)
+}
+
+/// Selects the result with the lowest penalty out of the results of several runs
+///
+/// The alternative schedules found by the other runs are offered to the alternative archive of
+/// the selected result
+///
+/// # Arguments
+/// * `results` - The results of the runs
+///
+/// # Returns
+/// * (Schedule, u32, OptimisationStats) - The best result
+/// * GlobalSearchError - If there are no results
+pub fn select_best_result(
+    mut results: Vec<HillClimbResult>,
+) -> Result<HillClimbResult, GlobalSearchError> {
+    let best_index = results
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, (_, total_penalty, _))| *total_penalty)
+        .map(|(i, _)| i)
+        .ok_or(GlobalSearchError::NoResults)?;
+
+    let (schedule, total_penalty, mut stats) = results.swap_remove(best_index);
+    for (_, _, run_stats) in &results {
+        stats
+            .alternative_archive
+            .merge(&run_stats.alternative_archive);
+    }
+
+    Ok((schedule, total_penalty, stats))
 }
@@ -0,0 +1,138 @@
+use log::{debug, error, info};
+use rayon::prelude::*;
+
+use crate::{
+    config::{Config, MigrationPolicy},
+    constraints::constraint_store::ConstraintStore,
+    global_search::{GlobalSearchError, HillClimbResult, select_best_result},
+    hill_climber::HillClimber,
+    schedule::Schedule,
+    stats::IslandStats,
+    stop_condition::StopCondition,
+};
+
+/// Runs an island model search
+///
+/// Each island is an annealing run started from its own quasi random schedule. Every
+/// `migration_interval` iterations the best schedule found across all islands is shared, and the
+/// `restarted_islands` weakest islands restart from it (or from a perturbed copy of it, depending
+/// on the migration policy)
+///
+/// # Arguments
+/// * `constraints` (&ConstraintStore) - The constraint store containing the constraints to
+///   schedule
+/// * `config` (&Config) - The configuration of the search
+/// * `stop_condition` (&StopCondition) - The stop condition of the whole global search
+///
+/// # Returns
+/// * (Schedule, u32, OptimisationStats) - The best schedule found by any of the islands, its
+///   penalty and the statistics of that island, including the statistics of all islands
+/// * GlobalSearchError - If any of the islands failed
+pub fn run_islands(
+    constraints: &ConstraintStore,
+    config: &Config,
+    stop_condition: &StopCondition,
+) -> Result<HillClimbResult, GlobalSearchError> {
+    let island_config = &config.island_config;
+    info!(
+        "Running {} islands with migration every {} iterations",
+        config.number_of_global_searches, island_config.migration_interval
+    );
+
+    let mut islands: Vec<HillClimber> = (0..config.number_of_global_searches)
+        .map(|i| {
+            let initial_schedule = Schedule::random(constraints, config.random_seed, Some(i));
+            HillClimber::new(constraints, initial_schedule, config)
+        })
+        .collect();
+    let mut island_stats: Vec<IslandStats> = (0..config.number_of_global_searches)
+        .map(|island| IslandStats {
+            island,
+            ..IslandStats::default()
+        })
+        .collect();
+
+    let run_stop_condition = stop_condition.for_run(config.time_limit_config.run_limit());
+    let migration_interval = island_config.migration_interval.max(1);
+    let mut completed_iterations = 0;
+
+    while completed_iterations < config.iterations {
+        let iterations = migration_interval.min(config.iterations - completed_iterations);
+        let results: Vec<Result<bool, String>> = islands
+            .par_iter_mut()
+            .map(|island| {
+                island
+                    .run(iterations, &run_stop_condition)
+                    .map(|stop_reason| stop_reason.is_some())
+                    .map_err(|error| error.to_string())
+            })
+            .collect();
+
+        let mut stopped = false;
+        for result in results {
+            match result {
+                Ok(island_stopped) => stopped |= island_stopped,
+                Err(error) => {
+                    error!("Error occurred executing island: {}", error);
+                    return Err(GlobalSearchError::NoResults);
+                }
+            }
+        }
+
+        completed_iterations += iterations;
+        if stopped || islands.iter().any(|island| island.best_total_penalty() == 0) {
+            break;
+        }
+
+        migrate(constraints, config, &mut islands, &mut island_stats);
+    }
+
+    for (island, stats) in islands.iter().zip(island_stats.iter_mut()) {
+        stats.best_total_penalty = island.best_total_penalty();
+    }
+
+    let (schedule, total_penalty, mut stats) =
+        select_best_result(islands.into_iter().map(HillClimber::into_result).collect())?;
+    stats.islands = island_stats;
+
+    info!("Island search complete. Identified best schedule");
+    Ok((schedule, total_penalty, stats))
+}
+
+/// Restarts the weakest islands from the best schedule found across all islands
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints to schedule
+/// * `config` - The configuration of the search
+/// * `islands` - The islands of the search
+/// * `island_stats` - The statistics of each of the islands
+fn migrate(
+    constraints: &ConstraintStore,
+    config: &Config,
+    islands: &mut [HillClimber],
+    island_stats: &mut [IslandStats],
+) {
+    let mut ranking: Vec<usize> = (0..islands.len()).collect();
+    ranking.sort_by_key(|i| islands[*i].best_total_penalty());
+
+    let Some((&source, weakest)) = ranking.split_first() else {
+        return;
+    };
+    let global_best = islands[source].best_schedule().clone();
+    island_stats[source].migrations_sent += 1;
+
+    for &island in weakest
+        .iter()
+        .rev()
+        .take(config.island_config.restarted_islands as usize)
+    {
+        let mut migrant = global_best.clone();
+        if config.island_config.migration_policy == MigrationPolicy::PerturbedGlobalBest {
+            migrant.perturb(constraints, config.island_config.perturbation_size);
+        }
+
+        debug!("Migrating best schedule of island {} to island {}", source, island);
+        islands[island].restart_from(migrant);
+        island_stats[island].migrations_received += 1;
+    }
+}
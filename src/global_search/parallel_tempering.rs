@@ -0,0 +1,154 @@
+use log::{debug, error, info};
+use rand::random;
+use rayon::prelude::*;
+
+use crate::{
+    config::{AcceptanceConfig, AcceptanceCriterion, AnnealingConfig, Config, RestartPolicy},
+    constraints::constraint_store::ConstraintStore,
+    global_search::{GlobalSearchError, HillClimbResult, select_best_result},
+    hill_climber::HillClimber,
+    schedule::Schedule,
+    stats::ReplicaExchangeStats,
+    stop_condition::StopCondition,
+};
+
+/// Runs a parallel tempering (replica exchange) search
+///
+/// Each chain runs the hill climber at its own fixed temperature, accepting changes with the
+/// Metropolis criterion. Every `exchange_interval` iterations, chains at neighbouring temperatures
+/// attempt to exchange their schedules. The exchange is accepted with probability
+/// `min(1, exp((1 / t_cold - 1 / t_hot) * (penalty_cold - penalty_hot)))`, so good schedules found
+/// by the hotter (exploring) chains can sink down to the colder (refining) chains
+///
+/// # Arguments
+/// * `constraints` (&ConstraintStore) - The constraint store containing the constraints to
+///   schedule
+/// * `config` (&Config) - The configuration of the search
+/// * `stop_condition` (&StopCondition) - The stop condition of the whole global search
+///
+/// # Returns
+/// * (Schedule, u32, OptimisationStats) - The best schedule found by any of the chains, its penalty
+///   and the statistics of that chain, including the exchange statistics of all chains
+/// * GlobalSearchError - If any of the chains failed
+pub fn run_parallel_tempering(
+    constraints: &ConstraintStore,
+    config: &Config,
+    stop_condition: &StopCondition,
+) -> Result<HillClimbResult, GlobalSearchError> {
+    let parallel_tempering_config = &config.parallel_tempering_config;
+    let temperatures =
+        parallel_tempering_config.temperature_ladder(config.number_of_global_searches);
+    info!("Running parallel tempering with temperatures {:?}", temperatures);
+
+    let chain_configs: Vec<Config> = temperatures
+        .iter()
+        .map(|temperature| Config {
+            initial_temperature: *temperature,
+            cooling_factor: 1.0,
+            annealing_config: AnnealingConfig {
+                restart_policy: RestartPolicy::Never,
+                ..AnnealingConfig::default()
+            },
+            acceptance_config: AcceptanceConfig {
+                criterion: AcceptanceCriterion::Metropolis,
+                ..AcceptanceConfig::default()
+            },
+            ..config.clone()
+        })
+        .collect();
+
+    let mut chains: Vec<HillClimber> = chain_configs
+        .iter()
+        .enumerate()
+        .map(|(i, chain_config)| {
+            let initial_schedule = Schedule::random(constraints, config.random_seed, Some(i as u32));
+            HillClimber::new(constraints, initial_schedule, chain_config)
+        })
+        .collect();
+
+    let mut exchanges: Vec<ReplicaExchangeStats> = temperatures
+        .windows(2)
+        .map(|pair| ReplicaExchangeStats {
+            lower_temperature: pair[0],
+            upper_temperature: pair[1],
+            ..ReplicaExchangeStats::default()
+        })
+        .collect();
+
+    let run_stop_condition = stop_condition.for_run(config.time_limit_config.run_limit());
+    let exchange_interval = parallel_tempering_config.exchange_interval.max(1);
+    let mut completed_iterations = 0;
+    let mut exchange_round = 0;
+
+    while completed_iterations < config.iterations {
+        let iterations = exchange_interval.min(config.iterations - completed_iterations);
+        let results: Vec<Result<bool, String>> = chains
+            .par_iter_mut()
+            .map(|chain| {
+                chain
+                    .run(iterations, &run_stop_condition)
+                    .map(|stop_reason| stop_reason.is_some())
+                    .map_err(|error| error.to_string())
+            })
+            .collect();
+
+        let mut stopped = false;
+        for result in results {
+            match result {
+                Ok(chain_stopped) => stopped |= chain_stopped,
+                Err(error) => {
+                    error!("Error occurred executing parallel tempering chain: {}", error);
+                    return Err(GlobalSearchError::NoResults);
+                }
+            }
+        }
+
+        completed_iterations += iterations;
+        if stopped || chains.iter().any(|chain| chain.best_total_penalty() == 0) {
+            break;
+        }
+
+        // NOTE: Alternating between even and odd pairs lets a schedule travel across several
+        // temperatures over consecutive rounds without being exchanged twice in the same round
+        for i in (exchange_round % 2..chains.len().saturating_sub(1)).step_by(2) {
+            exchanges[i].attempt_count += 1;
+            if !should_exchange(&chains[i], &chains[i + 1]) {
+                continue;
+            }
+
+            debug!(
+                "Exchanging schedules between temperatures {:?} and {:?}",
+                chains[i].temperature(),
+                chains[i + 1].temperature()
+            );
+            exchanges[i].accepted_count += 1;
+            let (colder, hotter) = chains.split_at_mut(i + 1);
+            colder[i].exchange_schedule(&mut hotter[0]);
+        }
+        exchange_round += 1;
+    }
+
+    let (schedule, total_penalty, mut stats) =
+        select_best_result(chains.into_iter().map(HillClimber::into_result).collect())?;
+    stats.replica_exchanges = exchanges;
+
+    info!("Parallel tempering complete. Identified best schedule");
+    Ok((schedule, total_penalty, stats))
+}
+
+/// Evaluates the replica exchange criterion between two chains
+///
+/// # Arguments
+/// * `colder` - The chain at the lower temperature
+/// * `hotter` - The chain at the higher temperature
+///
+/// # Returns
+/// * `true` - If the chains should exchange their schedules
+/// * `false` - Otherwise
+fn should_exchange(colder: &HillClimber, hotter: &HillClimber) -> bool {
+    let inverse_temperature_delta = 1.0 / colder.temperature() - 1.0 / hotter.temperature();
+    let penalty_delta = colder.total_penalty() as f32 - hotter.total_penalty() as f32;
+    let exponent = inverse_temperature_delta * penalty_delta;
+
+    exponent >= 0.0 || exponent.exp() > random()
+}
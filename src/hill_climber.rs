@@ -1,18 +1,25 @@
+pub mod acceptance;
 pub mod change_types;
+pub mod cooling;
 pub mod make_small_change;
 mod optimisation_strategies;
+pub mod strategy_selection;
 
-use std::error::Error;
+use std::{collections::HashMap, error::Error, mem};
 
 use crate::{
-    config::{OptimisationStrategyConfig, PenaltiesConfig},
+    config::{AnnealingConfig, Config, PenaltiesConfig, RestartPolicy, StrategySelection},
     constraints::{constraint_store::ConstraintStore, penalties::calculate_penalties},
+    alternatives::AlternativeArchive,
     schedule::Schedule,
-    stats::OptimisationStats,
+    stats::{OptimisationStats, StrategyWeightSnapshot},
+    stop_condition::{StopCondition, StopReason},
 };
-use log::debug;
+use acceptance::Acceptance;
+use cooling::Cooling;
+use log::{debug, info};
 use make_small_change::evolve_schedule;
-use rand::random;
+use strategy_selection::{StrategyOutcome, StrategySelector};
 
 /// Runs hill climbing optimisation algorithm to generate a schedule to satisfy specified
 /// constraints
@@ -20,123 +27,314 @@ use rand::random;
 /// # Arguments
 /// * `constraints` - The constraints store containing all the constraints to satisfy
 /// * `initial_schedule` - The initial schedule to start the algorithm with
-/// * `iterations` - The number of iterations to run the optimisation algorithm for
-/// * `temperature` - The initial temperature to run the hill climber with
-/// * `cooling_factor` - The cooling factor for the temperature
-/// * `penalties_config (&PenaltiesConfig)` - The configuration specifying values used for penalties
-///     during optimisation
-/// * `optimisation_strategy_config (OptimisationStrategyConfig)` - The configuration speciifying
-///     chances used in choosing optimisation strategy
+/// * `config` - The configuration specifying the iterations, temperature, cooling schedule,
+///   acceptance criterion, penalties and optimisation strategy chances of the run
+/// * `stop_condition` - The wall-clock/interrupt based condition checked before every iteration
 ///
 /// # Returns
 /// * Schedule - The output of the optimisation algorithm
 pub fn run_hill_climber(
-    constraints: &mut ConstraintStore,
+    constraints: &ConstraintStore,
     initial_schedule: Schedule,
-    iterations: u32,
-    initial_temperature: f32,
-    cooling_factor: f32,
-    penalties_config: &PenaltiesConfig,
-    optimisation_strategy_config: &OptimisationStrategyConfig,
+    config: &Config,
+    stop_condition: &StopCondition,
 ) -> Result<(Schedule, u32, OptimisationStats), Box<dyn Error>> {
-    let mut schedule = initial_schedule;
-    let (mut penalties, mut total_penalty) =
-        calculate_penalties(constraints, &schedule, penalties_config);
-    let mut temperature = initial_temperature;
-    let mut stagnant_counter = 0;
-    let mut stats = OptimisationStats::default();
+    let mut hill_climber = HillClimber::new(constraints, initial_schedule, config);
+    hill_climber.run(config.iterations, stop_condition)?;
 
-    let mut best_schedule = schedule.clone();
-    let mut best_total_penalty = total_penalty;
+    Ok(hill_climber.into_result())
+}
+
+/// The state of a single hill climbing run
+///
+/// Unlike `run_hill_climber`, the run can be advanced a number of iterations at a time, which
+/// allows several runs to exchange information between their iterations
+pub struct HillClimber<'a> {
+    constraints: &'a ConstraintStore,
+    annealing_config: &'a AnnealingConfig,
+    penalties_config: &'a PenaltiesConfig,
+    strategy_selector: StrategySelector<'a>,
+    cooling: Cooling<'a>,
+    acceptance: Acceptance<'a>,
+    schedule: Schedule,
+    penalties: HashMap<u32, u32>,
+    total_penalty: u32,
+    best_schedule: Schedule,
+    best_total_penalty: u32,
+    stagnant_counter: u32,
+    iteration: u32,
+    stats: OptimisationStats,
+}
 
-    for iteration in 0..iterations {
-        debug!("Running iteration number {:?}", iteration);
+impl<'a> HillClimber<'a> {
+    /// Creates a hill climbing run starting from the specified schedule
+    ///
+    /// # Arguments
+    /// * `constraints` - The constraints store containing all the constraints to satisfy
+    /// * `initial_schedule` - The initial schedule to start the algorithm with
+    /// * `config` - The configuration specifying the iterations, temperature, cooling schedule,
+    ///   acceptance criterion, penalties and optimisation strategy chances of the run
+    pub fn new(
+        constraints: &'a ConstraintStore,
+        initial_schedule: Schedule,
+        config: &'a Config,
+    ) -> Self {
+        let (penalties, total_penalty) =
+            calculate_penalties(constraints, &initial_schedule, &config.penalties_config);
 
-        if total_penalty == 0 {
-            break;
+        HillClimber {
+            constraints,
+            annealing_config: &config.annealing_config,
+            penalties_config: &config.penalties_config,
+            strategy_selector: StrategySelector::new(&config.optimisation_strategy_config),
+            cooling: Cooling::new(
+                &config.annealing_config,
+                config.initial_temperature,
+                config.cooling_factor,
+                config.iterations,
+            ),
+            acceptance: Acceptance::new(&config.acceptance_config, total_penalty),
+            best_schedule: initial_schedule.clone(),
+            best_total_penalty: total_penalty,
+            schedule: initial_schedule,
+            penalties,
+            total_penalty,
+            stagnant_counter: 0,
+            iteration: 0,
+            stats: OptimisationStats {
+                alternative_archive: AlternativeArchive::new(&config.alternatives_config),
+                ..OptimisationStats::default()
+            },
         }
+    }
 
-        temperature *= cooling_factor;
+    /// Runs the algorithm for the specified number of iterations
+    ///
+    /// Stops early if a schedule without any penalty is found or the stop condition is met
+    ///
+    /// # Arguments
+    /// * `iterations` - The number of iterations to run the optimisation algorithm for
+    /// * `stop_condition` - The wall-clock/interrupt based condition checked before every iteration
+    ///
+    /// # Returns
+    /// * `StopReason` - If the run was stopped by the stop condition
+    /// * `None` - Otherwise
+    pub fn run(
+        &mut self,
+        iterations: u32,
+        stop_condition: &StopCondition,
+    ) -> Result<Option<StopReason>, Box<dyn Error>> {
+        for _ in 0..iterations {
+            if self.total_penalty == 0 {
+                break;
+            }
+
+            if let Some(stop_reason) = stop_condition.check() {
+                info!(
+                    "Stopping run at iteration {:?} ({})",
+                    self.iteration, stop_reason
+                );
+                self.stats.stop_reason = Some(stop_reason);
+                return Ok(Some(stop_reason));
+            }
+
+            self.step()?;
+        }
+
+        Ok(None)
+    }
+
+    /// Runs a single iteration of the algorithm
+    fn step(&mut self) -> Result<(), Box<dyn Error>> {
+        debug!("Running iteration number {:?}", self.iteration);
+        self.iteration += 1;
+        self.cooling.cool();
+        self.log_strategy_weights();
 
         let Some(changes) = evolve_schedule(
-            constraints,
-            &penalties,
-            &mut schedule,
-            &mut stats,
-            optimisation_strategy_config,
+            self.constraints,
+            &self.penalties,
+            &mut self.schedule,
+            &mut self.stats,
+            &mut self.strategy_selector,
         )?
         else {
             debug!(
                 "Did not find optimisation schedule at iteration {:?}",
-                iteration
+                self.iteration
             );
-            stagnant_counter += 1;
-            continue;
+            self.stagnant_counter += 1;
+            self.strategy_selector
+                .record_outcome(StrategyOutcome::Rejected);
+            return Ok(());
         };
 
         let (new_penalties, new_total_penalty) =
-            calculate_penalties(constraints, &schedule, penalties_config);
+            calculate_penalties(self.constraints, &self.schedule, self.penalties_config);
         debug!("Evaluated penalty. Penalty: {:?}", new_total_penalty);
 
-        if !_should_accept_schedule(new_total_penalty, total_penalty, temperature) {
+        let accepted = self.acceptance.should_accept(
+            new_total_penalty,
+            self.total_penalty,
+            self.cooling.temperature(),
+        );
+        self.strategy_selector.record_outcome(if !accepted {
+            StrategyOutcome::Rejected
+        } else if new_total_penalty < self.total_penalty {
+            StrategyOutcome::Improved
+        } else {
+            StrategyOutcome::Accepted
+        });
+        if self.cooling.record_acceptance(accepted) {
+            debug!(
+                "Acceptance ratio too low. Reheated to {:?}",
+                self.cooling.temperature()
+            );
+            self.stats.reheat_count += 1;
+        }
+
+        if !accepted {
             debug!("Evolution not accepted. Reverting");
-            stagnant_counter += 1;
-            stats.revert_count += 1;
+            self.stagnant_counter += 1;
+            self.stats.revert_count += 1;
             changes
                 .iter()
                 .rev()
-                .for_each(|change| change.revert_change(&mut schedule));
+                .for_each(|change| change.revert_change(&mut self.schedule));
 
-            if stagnant_counter >= 500 {
-                stats.reset_count += 1;
-                stagnant_counter = 0;
-                temperature = initial_temperature;
+            if self.annealing_config.restart_policy != RestartPolicy::Never
+                && self.stagnant_counter >= self.annealing_config.stagnation_threshold
+            {
+                self.restart();
             }
-            continue;
+            return Ok(());
         };
 
-        total_penalty = new_total_penalty;
-        penalties = new_penalties;
+        self.total_penalty = new_total_penalty;
+        self.penalties = new_penalties;
+        self.stats
+            .alternative_archive
+            .offer(&self.schedule, self.total_penalty);
 
-        if total_penalty <= best_total_penalty {
-            best_total_penalty = total_penalty;
-            best_schedule = schedule.clone();
+        if self.total_penalty <= self.best_total_penalty {
+            self.best_total_penalty = self.total_penalty;
+            self.best_schedule = self.schedule.clone();
         }
+
+        Ok(())
     }
 
-    Ok((best_schedule, best_total_penalty, stats))
-}
+    /// Records the weights of the optimisation strategies every `weight_log_interval` iterations
+    ///
+    /// Nothing is recorded when the strategies are chosen with fixed chances
+    fn log_strategy_weights(&mut self) {
+        let optimisation_strategy_config = self.strategy_selector.config();
+        if optimisation_strategy_config.selection == StrategySelection::Fixed
+            || !self
+                .iteration
+                .is_multiple_of(optimisation_strategy_config.weight_log_interval)
+        {
+            return;
+        }
 
-/// Evaluated whether an evolution in the schedule should be accepted or not based on the realised
-/// penalties
-///
-/// # Arguments
-/// * `new_total_penalty` - The penalties realised for the new schedule
-/// * `existing_total_penalty` - The penalties realised for the schedule before the evolution
-/// * `temperature` - The current temperature in the annealing algorithm
-///
-/// # Returns
-/// * `true` - If the evolution should be accepted
-/// * `false` - Otherwise
-fn _should_accept_schedule(
-    new_total_penalty: u32,
-    existing_total_penalty: u32,
-    temperature: f32,
-) -> bool {
-    let delta_penalty: f32 = new_total_penalty as f32 - existing_total_penalty as f32;
-
-    if delta_penalty <= 0.0 {
-        return true;
+        self.stats.strategy_weight_history.push(StrategyWeightSnapshot {
+            iteration: self.iteration,
+            weights: self.strategy_selector.weights(),
+        });
     }
 
-    // NOTE: This is to prevent a divide by zero error
-    if temperature < 0.00001 {
-        return false;
-    };
+    /// Applies the configured restart policy
+    fn restart(&mut self) {
+        self.stats.reset_count += 1;
+        self.stagnant_counter = 0;
+        self.cooling.reset();
 
-    if (-delta_penalty / temperature).exp() > random() {
-        return true;
+        if self.annealing_config.restart_policy == RestartPolicy::RestartFromBest {
+            debug!(
+                "Restarting from best schedule. Penalty: {:?}",
+                self.best_total_penalty
+            );
+            self.set_schedule(self.best_schedule.clone());
+        }
+        self.acceptance.reset(self.total_penalty);
     }
 
-    false
+    /// Replaces the current schedule of the run, keeping the temperature
+    ///
+    /// The best schedule is updated if the new schedule improves on it
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule to carry on the run from
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        (self.penalties, self.total_penalty) =
+            calculate_penalties(self.constraints, &schedule, self.penalties_config);
+        self.schedule = schedule;
+
+        if self.total_penalty < self.best_total_penalty {
+            self.best_total_penalty = self.total_penalty;
+            self.best_schedule = self.schedule.clone();
+        }
+    }
+
+    /// Restarts the run from the specified schedule
+    ///
+    /// Unlike `set_schedule`, the temperature and the acceptance criterion are reset as well
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule to restart the run from
+    pub fn restart_from(&mut self, schedule: Schedule) {
+        self.stagnant_counter = 0;
+        self.cooling.reset();
+        self.set_schedule(schedule);
+        self.acceptance.reset(self.total_penalty);
+    }
+
+    /// Exchanges the current schedules of two runs, each run keeping its own temperature
+    ///
+    /// # Arguments
+    /// * `other` - The run to exchange the current schedule with
+    pub fn exchange_schedule(&mut self, other: &mut HillClimber<'a>) {
+        mem::swap(&mut self.schedule, &mut other.schedule);
+        mem::swap(&mut self.penalties, &mut other.penalties);
+        mem::swap(&mut self.total_penalty, &mut other.total_penalty);
+
+        for run in [self, other] {
+            if run.total_penalty < run.best_total_penalty {
+                run.best_total_penalty = run.total_penalty;
+                run.best_schedule = run.schedule.clone();
+            }
+        }
+    }
+
+    /// Returns the current temperature of the run
+    pub fn temperature(&self) -> f32 {
+        self.cooling.temperature()
+    }
+
+    /// Returns the penalty of the current schedule of the run
+    pub fn total_penalty(&self) -> u32 {
+        self.total_penalty
+    }
+
+    /// Returns the best schedule found by the run so far
+    pub fn best_schedule(&self) -> &Schedule {
+        &self.best_schedule
+    }
+
+    /// Returns the penalty of the best schedule found by the run so far
+    pub fn best_total_penalty(&self) -> u32 {
+        self.best_total_penalty
+    }
+
+    /// Finishes the run
+    ///
+    /// # Returns
+    /// * (Schedule, u32, OptimisationStats) - The best schedule found, its penalty and the
+    ///   statistics of the run
+    pub fn into_result(mut self) -> (Schedule, u32, OptimisationStats) {
+        if self.strategy_selector.config().selection != StrategySelection::Fixed {
+            self.stats.strategy_operators = self.strategy_selector.operator_stats();
+        }
+        (self.best_schedule, self.best_total_penalty, self.stats)
+    }
 }
@@ -0,0 +1,128 @@
+use rand::random;
+
+use crate::config::{AcceptanceConfig, AcceptanceCriterion};
+
+/// Keeps track of the state required by the configured acceptance criterion
+pub struct Acceptance<'a> {
+    acceptance_config: &'a AcceptanceConfig,
+    // Past penalties of the current schedule, used by late acceptance hill climbing
+    history: Vec<u32>,
+    history_index: usize,
+    // Penalty level under which schedules are accepted, used by great deluge
+    water_level: f32,
+}
+
+impl<'a> Acceptance<'a> {
+    /// Creates the acceptance state for a run starting from a schedule with the given penalty
+    ///
+    /// # Arguments
+    /// * `acceptance_config` - The configuration specifying the acceptance criterion
+    /// * `initial_total_penalty` - The penalty of the schedule the run starts with
+    pub fn new(acceptance_config: &'a AcceptanceConfig, initial_total_penalty: u32) -> Self {
+        let mut acceptance = Acceptance {
+            acceptance_config,
+            history: Vec::new(),
+            history_index: 0,
+            water_level: 0.0,
+        };
+        acceptance.reset(initial_total_penalty);
+        acceptance
+    }
+
+    /// Evaluates whether an evolution in the schedule should be accepted or not based on the
+    /// realised penalties
+    ///
+    /// # Arguments
+    /// * `new_total_penalty` - The penalties realised for the new schedule
+    /// * `existing_total_penalty` - The penalties realised for the schedule before the evolution
+    /// * `temperature` - The current temperature in the annealing algorithm
+    ///
+    /// # Returns
+    /// * `true` - If the evolution should be accepted
+    /// * `false` - Otherwise
+    pub fn should_accept(
+        &mut self,
+        new_total_penalty: u32,
+        existing_total_penalty: u32,
+        temperature: f32,
+    ) -> bool {
+        match self.acceptance_config.criterion {
+            AcceptanceCriterion::Metropolis => {
+                metropolis_criterion(new_total_penalty, existing_total_penalty, temperature)
+            }
+            AcceptanceCriterion::LateAcceptance => {
+                let late_penalty = self.history[self.history_index];
+                let accepted = new_total_penalty <= existing_total_penalty
+                    || new_total_penalty <= late_penalty;
+
+                self.history[self.history_index] = if accepted {
+                    new_total_penalty
+                } else {
+                    existing_total_penalty
+                };
+                self.history_index = (self.history_index + 1) % self.history.len();
+                accepted
+            }
+            AcceptanceCriterion::ThresholdAccepting => {
+                (new_total_penalty as f32 - existing_total_penalty as f32) <= temperature
+            }
+            AcceptanceCriterion::GreatDeluge => {
+                let accepted = new_total_penalty <= existing_total_penalty
+                    || new_total_penalty as f32 <= self.water_level;
+                self.water_level =
+                    (self.water_level - self.acceptance_config.great_deluge_rain_speed).max(0.0);
+                accepted
+            }
+        }
+    }
+
+    /// Resets the state of the acceptance criterion, typically when the algorithm is restarted
+    ///
+    /// # Arguments
+    /// * `total_penalty` - The penalty of the schedule the algorithm carries on from
+    pub fn reset(&mut self, total_penalty: u32) {
+        let history_length = self
+            .acceptance_config
+            .late_acceptance_history_length
+            .max(1) as usize;
+        self.history = vec![total_penalty; history_length];
+        self.history_index = 0;
+        self.water_level = total_penalty as f32;
+    }
+}
+
+/// Evaluates the Metropolis criterion used by simulated annealing
+///
+/// Improvements are always accepted, worse schedules are accepted with probability
+/// `exp(-delta / temperature)`
+///
+/// # Arguments
+/// * `new_total_penalty` - The penalties realised for the new schedule
+/// * `existing_total_penalty` - The penalties realised for the schedule before the evolution
+/// * `temperature` - The current temperature in the annealing algorithm
+///
+/// # Returns
+/// * `true` - If the evolution should be accepted
+/// * `false` - Otherwise
+fn metropolis_criterion(
+    new_total_penalty: u32,
+    existing_total_penalty: u32,
+    temperature: f32,
+) -> bool {
+    let delta_penalty: f32 = new_total_penalty as f32 - existing_total_penalty as f32;
+
+    if delta_penalty <= 0.0 {
+        return true;
+    }
+
+    // NOTE: This is to prevent a divide by zero error
+    if temperature < 0.00001 {
+        return false;
+    };
+
+    if (-delta_penalty / temperature).exp() > random() {
+        return true;
+    }
+
+    false
+}
@@ -0,0 +1,114 @@
+use std::f32::consts::E;
+
+use crate::config::{AnnealingConfig, CoolingSchedule};
+
+/// Keeps track of the temperature of the annealing algorithm under the configured cooling schedule
+pub struct Cooling<'a> {
+    annealing_config: &'a AnnealingConfig,
+    initial_temperature: f32,
+    cooling_factor: f32,
+    iterations: u32,
+    temperature: f32,
+    // Number of iterations since the start of the run or the last reset
+    step: u32,
+    // Acceptance tracking used by the adaptive cooling schedule
+    evaluated_in_window: u32,
+    accepted_in_window: u32,
+}
+
+impl<'a> Cooling<'a> {
+    /// Creates the cooling state at the initial temperature
+    ///
+    /// # Arguments
+    /// * `annealing_config` - The configuration specifying the cooling schedule
+    /// * `initial_temperature` - The temperature the annealing algorithm starts (and restarts) at
+    /// * `cooling_factor` - The cooling factor used by the geometric and adaptive schedules
+    /// * `iterations` - The number of iterations of the run, used by the linear schedule
+    pub fn new(
+        annealing_config: &'a AnnealingConfig,
+        initial_temperature: f32,
+        cooling_factor: f32,
+        iterations: u32,
+    ) -> Self {
+        Cooling {
+            annealing_config,
+            initial_temperature,
+            cooling_factor,
+            iterations,
+            temperature: initial_temperature,
+            step: 0,
+            evaluated_in_window: 0,
+            accepted_in_window: 0,
+        }
+    }
+
+    /// Returns the current temperature
+    pub fn temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    /// Lowers the temperature by a single iteration of the cooling schedule
+    pub fn cool(&mut self) {
+        self.step += 1;
+
+        self.temperature = match self.annealing_config.cooling_schedule {
+            CoolingSchedule::Geometric | CoolingSchedule::Adaptive => {
+                self.temperature * self.cooling_factor
+            }
+            CoolingSchedule::Linear => {
+                let decrement = self.initial_temperature / self.iterations.max(1) as f32;
+                (self.initial_temperature - decrement * self.step as f32).max(0.0)
+            }
+            CoolingSchedule::Logarithmic => self.initial_temperature / (E + self.step as f32).ln(),
+            CoolingSchedule::LundyMees => {
+                self.temperature / (1.0 + self.annealing_config.lundy_mees_beta * self.temperature)
+            }
+        };
+    }
+
+    /// Records the outcome of an evaluated change
+    ///
+    /// Only used by the adaptive cooling schedule, which reheats once the ratio of accepted changes
+    /// over the configured window falls below the target
+    ///
+    /// # Arguments
+    /// * `accepted` - Whether the evaluated change was accepted
+    ///
+    /// # Returns
+    /// * `true` - If the temperature was reheated
+    /// * `false` - Otherwise
+    pub fn record_acceptance(&mut self, accepted: bool) -> bool {
+        if self.annealing_config.cooling_schedule != CoolingSchedule::Adaptive {
+            return false;
+        }
+
+        self.evaluated_in_window += 1;
+        if accepted {
+            self.accepted_in_window += 1;
+        }
+
+        if self.evaluated_in_window < self.annealing_config.adaptive_window {
+            return false;
+        }
+
+        let acceptance_ratio = self.accepted_in_window as f32 / self.evaluated_in_window as f32;
+        self.evaluated_in_window = 0;
+        self.accepted_in_window = 0;
+
+        if acceptance_ratio >= self.annealing_config.adaptive_target_acceptance_ratio {
+            return false;
+        }
+
+        self.temperature = (self.temperature * self.annealing_config.adaptive_reheat_factor)
+            .min(self.initial_temperature);
+        true
+    }
+
+    /// Resets the temperature back to the initial temperature
+    pub fn reset(&mut self) {
+        self.temperature = self.initial_temperature;
+        self.step = 0;
+        self.evaluated_in_window = 0;
+        self.accepted_in_window = 0;
+    }
+}
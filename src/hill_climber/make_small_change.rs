@@ -1,8 +1,5 @@
 use log::debug;
-use rand::{rng, seq::IndexedRandom};
-
 use crate::{
-    config::OptimisationStrategyConfig,
     constraints::constraint_store::ConstraintStore,
     hill_climber::{
         OptimisationStats,
@@ -11,6 +8,7 @@ use crate::{
             OptimisationStrategy, execute_move_strategy, execute_schedule_strategy,
             execute_substitute_strategy, execute_swap_strategy,
         },
+        strategy_selection::StrategySelector,
     },
     schedule::{Schedule, Slot},
 };
@@ -28,18 +26,18 @@ pub struct SchedulableSlots {
 ///  * `constraints` - The constraints store containing all constraints
 ///  * `incurred_penalties` - The penalties incurred by the constraints under the specified schedule
 ///  * `Schedule` - The current state of the schedule
-///  * `optimisation_strategy_config (OptimisationStrategyConfig)` - The configuration speciifying
-///     chances used in choosing optimisation strategy
+///  * `strategy_selector` - The selector choosing the optimisation strategy for scheduled
+///    constraints
 ///
 ///  # Returns
 ///  `ChangeType` - The type of change made if the state of the schedule is changed
 ///  `None` - If no state change was done on this iteration
 pub fn evolve_schedule(
-    constraints: &mut ConstraintStore,
+    constraints: &ConstraintStore,
     incurred_penalties: &HashMap<u32, u32>,
     schedule: &mut Schedule,
     stats: &mut OptimisationStats,
-    optimisation_strategy_config: &OptimisationStrategyConfig,
+    strategy_selector: &mut StrategySelector,
 ) -> Result<Option<Vec<ChangeType>>, Box<dyn Error>> {
     let constraint = constraints.get_constraint_for_optimisation(incurred_penalties)?;
 
@@ -59,7 +57,7 @@ pub fn evolve_schedule(
             schedulabe_slots_for_constraint,
             schedule,
             stats,
-            optimisation_strategy_config,
+            strategy_selector,
         ));
     } else {
         return Ok(handle_unscheduled_constraint(
@@ -77,36 +75,21 @@ pub fn evolve_schedule(
 /// # Arguments
 /// * constraint - The constraint to optimise
 /// * schedule - The current state of the schedule
-/// * `optimisation_strategy_config (OptimisationStrategyConfig)` - The configuration speciifying
-///     chances used in choosing optimisation strategy
+/// * `strategy_selector` - The selector choosing the optimisation strategy
 ///
 /// # Returns
 /// * Vec<ChangeType> - A vector containing all the changes performed (in order)
 /// * None - If no optimisation was performed
 fn handle_scheduled_constraint(
-    constraint_store: &mut ConstraintStore,
+    constraint_store: &ConstraintStore,
     constraint_id: u32,
     constraint_duration: u8,
     mut schedulable_slots: SchedulableSlots,
     schedule: &mut Schedule,
     stats: &mut OptimisationStats,
-    optimisation_strategy_config: &OptimisationStrategyConfig,
+    strategy_selector: &mut StrategySelector,
 ) -> Option<Vec<ChangeType>> {
-    let strategies = [
-        (
-            OptimisationStrategy::MOVE,
-            &optimisation_strategy_config.move_chance,
-        ),
-        (
-            OptimisationStrategy::UNSCHEDULE,
-            &optimisation_strategy_config.unschedule_chance,
-        ),
-        (
-            OptimisationStrategy::SWAP,
-            &optimisation_strategy_config.swap_chance,
-        ),
-    ];
-    let (option, _) = strategies.choose_weighted(&mut rng(), |s| s.1).unwrap();
+    let option = strategy_selector.choose();
 
     let current_slot = schedule
         .get_scheduled_slot_for_constraint(constraint_id)
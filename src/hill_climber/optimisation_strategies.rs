@@ -6,6 +6,7 @@ use crate::{
 };
 
 /// Enum defining the different optimisation strategies
+#[derive(Clone, Copy)]
 pub enum OptimisationStrategy {
     MOVE,
     SWAP,
@@ -50,7 +51,7 @@ pub fn execute_move_strategy(
 pub fn execute_swap_strategy(
     constraint_id: u32,
     constraint_duration: u8,
-    constraint_store: &mut ConstraintStore,
+    constraint_store: &ConstraintStore,
     schedule: &mut Schedule,
 ) -> Option<Vec<ChangeType>> {
     let swappable_constraint = constraint_store.find_swappable_scheduled_constraint(
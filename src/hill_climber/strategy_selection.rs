@@ -0,0 +1,193 @@
+use rand::{rng, seq::IndexedRandom};
+
+use super::optimisation_strategies::OptimisationStrategy;
+use crate::{
+    config::{OptimisationStrategyConfig, StrategySelection},
+    stats::OperatorStats,
+};
+
+/// The result of applying an optimisation strategy, used for rewarding the strategy
+pub enum StrategyOutcome {
+    Improved,
+    Accepted,
+    Rejected,
+}
+
+impl StrategyOutcome {
+    fn reward(&self) -> f32 {
+        match self {
+            StrategyOutcome::Improved => 1.0,
+            StrategyOutcome::Accepted => 0.1,
+            StrategyOutcome::Rejected => 0.0,
+        }
+    }
+}
+
+/// The state of a single strategy of the selector
+struct StrategyArm {
+    strategy: OptimisationStrategy,
+    name: &'static str,
+    enabled: bool,
+    weight: f32,
+    use_count: u32,
+    improvement_count: u32,
+}
+
+/// Chooses the optimisation strategy applied to scheduled constraints, adapting the chances of
+/// each strategy to the rewards they earn when the selection is not `Fixed`
+pub struct StrategySelector<'a> {
+    optimisation_strategy_config: &'a OptimisationStrategyConfig,
+    arms: Vec<StrategyArm>,
+    // The arm chosen by the last call to `choose`, waiting for its outcome
+    pending_arm: Option<usize>,
+    total_use_count: u32,
+}
+
+impl<'a> StrategySelector<'a> {
+    /// Creates a selector with the weights set to the configured (normalised) chances
+    ///
+    /// # Arguments
+    /// * `optimisation_strategy_config` - The configuration specifying the chances and selection
+    ///   method of the strategies
+    pub fn new(optimisation_strategy_config: &'a OptimisationStrategyConfig) -> Self {
+        let chances = [
+            (
+                OptimisationStrategy::MOVE,
+                "Move",
+                optimisation_strategy_config.move_chance,
+            ),
+            (
+                OptimisationStrategy::UNSCHEDULE,
+                "Unschedule",
+                optimisation_strategy_config.unschedule_chance,
+            ),
+            (
+                OptimisationStrategy::SWAP,
+                "Swap",
+                optimisation_strategy_config.swap_chance,
+            ),
+        ];
+        let total_chance: u32 = chances.iter().map(|(_, _, chance)| chance).sum();
+
+        StrategySelector {
+            optimisation_strategy_config,
+            arms: chances
+                .into_iter()
+                .map(|(strategy, name, chance)| StrategyArm {
+                    strategy,
+                    name,
+                    enabled: chance > 0,
+                    weight: chance as f32 / total_chance.max(1) as f32,
+                    use_count: 0,
+                    improvement_count: 0,
+                })
+                .collect(),
+            pending_arm: None,
+            total_use_count: 0,
+        }
+    }
+
+    /// Chooses the strategy to apply to a scheduled constraint
+    ///
+    /// # Returns
+    /// * `OptimisationStrategy` - The chosen strategy
+    pub fn choose(&mut self) -> OptimisationStrategy {
+        let enabled_arms: Vec<usize> = (0..self.arms.len())
+            .filter(|index| self.arms[*index].enabled)
+            .collect();
+
+        let index = match self.optimisation_strategy_config.selection {
+            StrategySelection::Fixed | StrategySelection::RouletteWheel => *enabled_arms
+                .choose_weighted(&mut rng(), |index| self.arms[*index].weight)
+                .expect("LOGIC ERROR: At least one optimisation strategy must be enabled"),
+            StrategySelection::Bandit => {
+                // NOTE: Every strategy is tried once before the UCB1 rule is applied
+                let untried_arm = enabled_arms
+                    .iter()
+                    .find(|index| self.arms[**index].use_count == 0);
+                match untried_arm {
+                    Some(index) => *index,
+                    None => *enabled_arms
+                        .iter()
+                        .max_by(|first, second| {
+                            self.upper_confidence_bound(**first)
+                                .total_cmp(&self.upper_confidence_bound(**second))
+                        })
+                        .expect("LOGIC ERROR: At least one optimisation strategy must be enabled"),
+                }
+            }
+        };
+
+        let arm = &mut self.arms[index];
+        arm.use_count += 1;
+        self.total_use_count += 1;
+        self.pending_arm = Some(index);
+        arm.strategy
+    }
+
+    /// Rewards the strategy chosen by the last call to `choose` according to its outcome
+    ///
+    /// Does nothing if no strategy was chosen since the last outcome (e.g. the optimised
+    /// constraint was unscheduled)
+    ///
+    /// # Arguments
+    /// * `outcome` - The outcome of applying the strategy
+    pub fn record_outcome(&mut self, outcome: StrategyOutcome) {
+        let Some(index) = self.pending_arm.take() else {
+            return;
+        };
+
+        let arm = &mut self.arms[index];
+        if let StrategyOutcome::Improved = outcome {
+            arm.improvement_count += 1;
+        }
+
+        // NOTE: For the bandit, the weight is the (recency weighted) average reward of the
+        // strategy, which the UCB1 rule balances against how rarely the strategy was tried
+        let reaction_factor = self.optimisation_strategy_config.reaction_factor;
+        match self.optimisation_strategy_config.selection {
+            StrategySelection::Fixed => {}
+            StrategySelection::RouletteWheel => {
+                // NOTE: The weight is kept above zero so that every strategy keeps a chance of
+                // being chosen
+                arm.weight =
+                    ((1.0 - reaction_factor) * arm.weight + reaction_factor * outcome.reward())
+                        .max(0.01);
+            }
+            StrategySelection::Bandit => {
+                arm.weight += reaction_factor * (outcome.reward() - arm.weight);
+            }
+        }
+    }
+
+    /// Calculates the UCB1 score of an arm that has been tried at least once
+    fn upper_confidence_bound(&self, index: usize) -> f32 {
+        let arm = &self.arms[index];
+        arm.weight
+            + self.optimisation_strategy_config.bandit_exploration
+                * ((self.total_use_count as f32).ln() / arm.use_count as f32).sqrt()
+    }
+
+    /// Returns the configuration of the selector
+    pub fn config(&self) -> &'a OptimisationStrategyConfig {
+        self.optimisation_strategy_config
+    }
+
+    /// Returns the current weight of every strategy (move, unschedule, swap)
+    pub fn weights(&self) -> Vec<f32> {
+        self.arms.iter().map(|arm| arm.weight).collect()
+    }
+
+    /// Returns the statistics of every strategy
+    pub fn operator_stats(&self) -> Vec<OperatorStats> {
+        self.arms
+            .iter()
+            .map(|arm| OperatorStats {
+                name: arm.name.to_string(),
+                weight: arm.weight,
+                use_count: arm.use_count,
+                improvement_count: arm.improvement_count,
+            })
+            .collect()
+    }
+}
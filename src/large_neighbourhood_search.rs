@@ -0,0 +1,189 @@
+pub mod recreate;
+mod ruin;
+
+use log::{debug, error, info};
+use rand::{rng, seq::IndexedMutRandom};
+use rayon::prelude::*;
+
+use crate::{
+    alternatives::AlternativeArchive,
+    config::Config,
+    constraints::{constraint_store::ConstraintStore, penalties::calculate_penalties},
+    global_search::{GlobalSearchError, HillClimbResult, select_best_result},
+    hill_climber::{acceptance::Acceptance, cooling::Cooling},
+    schedule::Schedule,
+    stats::{OperatorStats, OptimisationStats},
+    stop_condition::StopCondition,
+};
+use recreate::recreate;
+use ruin::{RuinOperator, ruin};
+
+/// Runs `number_of_global_searches` adaptive large neighbourhood searches in parallel and returns
+/// the best result
+///
+/// # Arguments
+/// * `constraints` (&ConstraintStore) - The constraint store containing the constraints to
+///   schedule
+/// * `config` (&Config) - The configuration of the search
+/// * `stop_condition` (&StopCondition) - The stop condition of the whole global search
+pub fn run_large_neighbourhood_search(
+    constraints: &ConstraintStore,
+    config: &Config,
+    stop_condition: &StopCondition,
+) -> Result<HillClimbResult, GlobalSearchError> {
+    let results: Vec<HillClimbResult> = (0..config.number_of_global_searches)
+        .into_par_iter()
+        .inspect(|_| info!("Running large neighbourhood search"))
+        .map(|i| {
+            let initial_schedule = Schedule::random(constraints, config.random_seed, Some(i));
+            run_adaptive_large_neighbourhood_search(
+                constraints,
+                initial_schedule,
+                config,
+                &stop_condition.for_run(config.time_limit_config.run_limit()),
+            )
+        })
+        .collect();
+
+    select_best_result(results)
+        .inspect(|_| info!("Large neighbourhood search complete. Identified best schedule"))
+        .inspect_err(|_| error!("No large neighbourhood searches were run"))
+}
+
+/// Runs a single adaptive large neighbourhood search (ALNS)
+///
+/// Every iteration, a ruin operator is chosen with probability proportional to its weight and
+/// unschedules a large part of the current schedule, which is then recreated. The recreated
+/// schedule replaces the current schedule according to the configured acceptance criterion, and
+/// the weight of the operator moves towards the reward it earned (new best schedule, improvement,
+/// accepted or nothing)
+///
+/// # Arguments
+/// * `constraints` - The constraints store containing all the constraints to satisfy
+/// * `initial_schedule` - The initial schedule to start the search with
+/// * `config` - The configuration of the search
+/// * `stop_condition` - The wall-clock/interrupt based condition checked before every iteration
+///
+/// # Returns
+/// * (Schedule, u32, OptimisationStats) - The best schedule found, its penalty and the statistics
+///   of the search
+fn run_adaptive_large_neighbourhood_search(
+    constraints: &ConstraintStore,
+    initial_schedule: Schedule,
+    config: &Config,
+    stop_condition: &StopCondition,
+) -> HillClimbResult {
+    let large_neighbourhood_config = &config.large_neighbourhood_config;
+    let mut stats = OptimisationStats {
+        alternative_archive: AlternativeArchive::new(&config.alternatives_config),
+        ..OptimisationStats::default()
+    };
+    let mut operators: Vec<(RuinOperator, OperatorStats)> = RuinOperator::ALL
+        .iter()
+        .map(|operator| {
+            (
+                *operator,
+                OperatorStats {
+                    name: operator.to_string(),
+                    weight: 1.0,
+                    ..OperatorStats::default()
+                },
+            )
+        })
+        .collect();
+
+    let mut schedule = initial_schedule;
+    let (_, mut total_penalty) =
+        calculate_penalties(constraints, &schedule, &config.penalties_config);
+    let mut best_schedule = schedule.clone();
+    let mut best_total_penalty = total_penalty;
+
+    let mut cooling = Cooling::new(
+        &config.annealing_config,
+        config.initial_temperature,
+        config.cooling_factor,
+        config.iterations,
+    );
+    let mut acceptance = Acceptance::new(&config.acceptance_config, total_penalty);
+
+    for iteration in 0..config.iterations {
+        if best_total_penalty == 0 {
+            break;
+        }
+
+        if let Some(stop_reason) = stop_condition.check() {
+            info!("Stopping run at iteration {:?} ({})", iteration, stop_reason);
+            stats.stop_reason = Some(stop_reason);
+            break;
+        }
+
+        cooling.cool();
+
+        let (operator, operator_stats) = operators
+            .choose_weighted_mut(&mut rng(), |(_, operator_stats)| operator_stats.weight)
+            .expect("LOGIC ERROR: Ruin operator weights must be positive");
+        operator_stats.use_count += 1;
+
+        let mut candidate = schedule.clone();
+        let ruined_constraints = ruin(
+            *operator,
+            &mut candidate,
+            constraints,
+            large_neighbourhood_config.time_band_width,
+        );
+        recreate(&mut candidate, constraints, config);
+
+        let (_, candidate_total_penalty) =
+            calculate_penalties(constraints, &candidate, &config.penalties_config);
+        debug!(
+            "Ruined {:?} constraints with {} operator. Penalty: {:?}",
+            ruined_constraints.len(),
+            operator,
+            candidate_total_penalty
+        );
+
+        let accepted = acceptance.should_accept(
+            candidate_total_penalty,
+            total_penalty,
+            cooling.temperature(),
+        );
+        let reward = if !accepted {
+            0.0
+        } else if candidate_total_penalty < best_total_penalty {
+            large_neighbourhood_config.global_best_reward
+        } else if candidate_total_penalty < total_penalty {
+            large_neighbourhood_config.improvement_reward
+        } else {
+            large_neighbourhood_config.acceptance_reward
+        };
+
+        // NOTE: The weight is kept above zero so that every operator keeps a chance of being chosen
+        let reaction_factor = large_neighbourhood_config.reaction_factor;
+        operator_stats.weight =
+            ((1.0 - reaction_factor) * operator_stats.weight + reaction_factor * reward).max(0.01);
+
+        if !accepted {
+            stats.revert_count += 1;
+            continue;
+        }
+
+        if candidate_total_penalty < total_penalty {
+            operator_stats.improvement_count += 1;
+        }
+
+        schedule = candidate;
+        total_penalty = candidate_total_penalty;
+        stats.alternative_archive.offer(&schedule, total_penalty);
+
+        if total_penalty < best_total_penalty {
+            best_total_penalty = total_penalty;
+            best_schedule = schedule.clone();
+        }
+    }
+
+    stats.ruin_operators = operators
+        .into_iter()
+        .map(|(_, operator_stats)| operator_stats)
+        .collect();
+    (best_schedule, best_total_penalty, stats)
+}
@@ -0,0 +1,333 @@
+use rand::{rng, seq::SliceRandom};
+
+use crate::{
+    config::{Config, LargeNeighbourhoodConfig, PenaltiesConfig, RecreateMethod},
+    constraints::{
+        Constraint, ConstraintPriority, constraint_store::ConstraintStore, penalty::Penalty,
+    },
+    schedule::{Schedule, Slot},
+};
+
+/// Schedules the unscheduled constraints of the schedule using the configured recreate method
+///
+/// Constraints are scheduled in order of priority and then duration (longest first), with ties
+/// broken at random
+///
+/// # Arguments
+/// * `schedule` - The (ruined) schedule to recreate
+/// * `constraints` - The constraint store containing all constraints
+/// * `config` - The configuration of the search
+pub fn recreate(schedule: &mut Schedule, constraints: &ConstraintStore, config: &Config) {
+    let mut unscheduled: Vec<&Constraint> = constraints
+        .into_iter()
+        .filter(|constraint| !schedule.is_constraint_scheduled(constraint.id))
+        .collect();
+    unscheduled.shuffle(&mut rng());
+    unscheduled.sort_by_key(|constraint| {
+        (
+            !matches!(constraint.priority, ConstraintPriority::High),
+            std::cmp::Reverse(constraint.duration),
+        )
+    });
+
+    let large_neighbourhood_config = &config.large_neighbourhood_config;
+    match large_neighbourhood_config.recreate_method {
+        RecreateMethod::Exact
+            if unscheduled.len() <= large_neighbourhood_config.exact_max_constraints as usize =>
+        {
+            recreate_exact(
+                schedule,
+                constraints,
+                &unscheduled,
+                large_neighbourhood_config,
+                &config.penalties_config,
+            )
+        }
+        _ => {
+            recreate_greedy(
+                schedule,
+                constraints,
+                &unscheduled,
+                &config.penalties_config,
+            );
+        }
+    }
+}
+
+/// Schedules each constraint, one at a time, in the slot that increases the penalty the least
+///
+/// A constraint is left unscheduled if scheduling it in any of the free slots would not decrease
+/// the penalty
+///
+/// # Arguments
+/// * `schedule` - The schedule to recreate
+/// * `constraints` - The constraint store containing all constraints
+/// * `unscheduled` - The constraints to schedule, in order
+/// * `penalties_config` - The configuration specifying values used for penalties
+///
+/// # Returns
+/// * `i64` - The change in the total penalty
+fn recreate_greedy(
+    schedule: &mut Schedule,
+    constraints: &ConstraintStore,
+    unscheduled: &[&Constraint],
+    penalties_config: &PenaltiesConfig,
+) -> i64 {
+    let mut total_cost = 0;
+
+    for constraint in unscheduled {
+        let best_insertion = candidate_slots(schedule, constraint.duration)
+            .into_iter()
+            .map(|slot| {
+                let cost =
+                    insertion_cost(schedule, constraints, constraint, &slot, penalties_config);
+                (slot, cost)
+            })
+            .min_by_key(|(_, cost)| *cost);
+
+        if let Some((slot, cost)) = best_insertion
+            && cost < 0
+        {
+            schedule.schedule_constraint(constraint.id, constraint.duration, &slot);
+            total_cost += cost;
+        }
+    }
+
+    total_cost
+}
+
+/// State shared by the nodes of the branch and bound search
+struct ExactSearch<'a> {
+    constraints: &'a ConstraintStore,
+    unscheduled: &'a [&'a Constraint],
+    penalties_config: &'a PenaltiesConfig,
+    candidates: Vec<Vec<Slot>>,
+    // lower_bounds[i] - A lower bound on the change in penalty from scheduling constraints i..
+    lower_bounds: Vec<i64>,
+    node_limit: u32,
+    nodes: u32,
+    assignment: Vec<Option<Slot>>,
+    best_assignment: Vec<Option<Slot>>,
+    best_cost: i64,
+}
+
+/// Schedules the constraints using a branch and bound search
+///
+/// Each constraint is either left unscheduled or scheduled in one of its `exact_candidate_slots`
+/// best slots (evaluated on the ruined schedule). The search starts from the greedy solution, so
+/// the result is never worse than the greedy method. If the node limit is reached, the best
+/// solution found so far is used
+///
+/// # Arguments
+/// * `schedule` - The schedule to recreate
+/// * `constraints` - The constraint store containing all constraints
+/// * `unscheduled` - The constraints to schedule, in order
+/// * `large_neighbourhood_config` - The configuration of the exact method
+/// * `penalties_config` - The configuration specifying values used for penalties
+fn recreate_exact(
+    schedule: &mut Schedule,
+    constraints: &ConstraintStore,
+    unscheduled: &[&Constraint],
+    large_neighbourhood_config: &LargeNeighbourhoodConfig,
+    penalties_config: &PenaltiesConfig,
+) {
+    let mut candidates = Vec::new();
+    let mut constraint_lower_bounds = Vec::new();
+    for constraint in unscheduled {
+        let mut evaluated: Vec<(Slot, i64)> = candidate_slots(schedule, constraint.duration)
+            .into_iter()
+            .map(|slot| {
+                let cost =
+                    insertion_cost(schedule, constraints, constraint, &slot, penalties_config);
+                (slot, cost)
+            })
+            .collect();
+        evaluated.sort_by_key(|(_, cost)| *cost);
+        evaluated.truncate(large_neighbourhood_config.exact_candidate_slots as usize);
+
+        let lower_bound = evaluated
+            .iter()
+            .map(|(slot, _)| {
+                slot_cost_lower_bound(schedule, constraints, constraint, slot, penalties_config)
+            })
+            .min()
+            .unwrap_or(0)
+            .min(0);
+        constraint_lower_bounds.push(lower_bound);
+        candidates.push(evaluated.into_iter().map(|(slot, _)| slot).collect());
+    }
+
+    let mut lower_bounds = vec![0; unscheduled.len() + 1];
+    for i in (0..unscheduled.len()).rev() {
+        lower_bounds[i] = lower_bounds[i + 1] + constraint_lower_bounds[i];
+    }
+
+    let mut greedy_schedule = schedule.clone();
+    let greedy_cost =
+        recreate_greedy(&mut greedy_schedule, constraints, unscheduled, penalties_config);
+
+    let mut search = ExactSearch {
+        constraints,
+        unscheduled,
+        penalties_config,
+        candidates,
+        lower_bounds,
+        node_limit: large_neighbourhood_config.exact_node_limit,
+        nodes: 0,
+        assignment: Vec::new(),
+        best_assignment: unscheduled
+            .iter()
+            .map(|constraint| {
+                greedy_schedule
+                    .get_scheduled_slot_for_constraint(constraint.id)
+                    .cloned()
+            })
+            .collect(),
+        best_cost: greedy_cost,
+    };
+    search.branch(schedule, 0);
+
+    for (constraint, slot) in unscheduled.iter().zip(search.best_assignment) {
+        if let Some(slot) = slot {
+            schedule.schedule_constraint(constraint.id, constraint.duration, &slot);
+        }
+    }
+}
+
+impl ExactSearch<'_> {
+    /// Explores the assignments of the constraints from `index` onwards
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule with the constraints before `index` assigned
+    /// * `cost` - The change in penalty from the assignments so far
+    fn branch(&mut self, schedule: &mut Schedule, cost: i64) {
+        self.nodes += 1;
+        if self.nodes > self.node_limit {
+            return;
+        }
+
+        let index = self.assignment.len();
+        if index == self.unscheduled.len() {
+            if cost < self.best_cost {
+                self.best_cost = cost;
+                self.best_assignment = self.assignment.clone();
+            }
+            return;
+        }
+
+        if cost + self.lower_bounds[index] >= self.best_cost {
+            return;
+        }
+
+        let constraint = self.unscheduled[index];
+        for slot in self.candidates[index].clone() {
+            if !schedule.is_duration_free(&slot, constraint.duration) {
+                continue;
+            }
+
+            let slot_cost = insertion_cost(
+                schedule,
+                self.constraints,
+                constraint,
+                &slot,
+                self.penalties_config,
+            );
+            schedule.schedule_constraint(constraint.id, constraint.duration, &slot);
+            self.assignment.push(Some(slot));
+            self.branch(schedule, cost + slot_cost);
+            self.assignment.pop();
+            schedule
+                .unschedule_constraint(constraint.id)
+                .expect("LOGIC ERROR: Could not unschedule scheduled constraint");
+        }
+
+        self.assignment.push(None);
+        self.branch(schedule, cost);
+        self.assignment.pop();
+    }
+}
+
+/// Returns every slot where a constraint of the specified duration can be scheduled without
+/// overlapping other constraints
+///
+/// # Arguments
+/// * `schedule` - The schedule to find the slots in
+/// * `duration` - The duration of the constraint
+pub fn candidate_slots(schedule: &Schedule, duration: u8) -> Vec<Slot> {
+    (0..7)
+        .flat_map(|day| (0..=48u8.saturating_sub(duration)).map(move |window| Slot { day, window }))
+        .filter(|slot| schedule.is_duration_free(slot, duration))
+        .collect()
+}
+
+/// Calculates the change in the total penalty caused by scheduling an unscheduled constraint in a
+/// free slot
+///
+/// Only constraints of the same type can be affected (through gap penalties), so only their
+/// penalties are evaluated
+///
+/// # Arguments
+/// * `schedule` - The schedule to schedule the constraint in. Left unchanged
+/// * `constraints` - The constraint store containing all constraints
+/// * `constraint` - The constraint to schedule
+/// * `slot` - The free slot to schedule the constraint in
+/// * `penalties_config` - The configuration specifying values used for penalties
+///
+/// # Returns
+/// * `i64` - The change in the total penalty
+pub fn insertion_cost(
+    schedule: &mut Schedule,
+    constraints: &ConstraintStore,
+    constraint: &Constraint,
+    slot: &Slot,
+    penalties_config: &PenaltiesConfig,
+) -> i64 {
+    let affected_constraints: Vec<&Constraint> = constraints
+        .get_constraint_ids_of_type(&constraint.name)
+        .into_iter()
+        .filter_map(|constraint_id| constraints.get_constraint(constraint_id))
+        .collect();
+    let penalty_of_affected = |schedule: &Schedule| -> i64 {
+        affected_constraints
+            .iter()
+            .map(|c| c.calculate_penalty(schedule, constraints, penalties_config) as i64)
+            .sum()
+    };
+
+    let penalty_before = penalty_of_affected(schedule);
+    schedule.schedule_constraint(constraint.id, constraint.duration, slot);
+    let penalty_after = penalty_of_affected(schedule);
+    schedule
+        .unschedule_constraint(constraint.id)
+        .expect("LOGIC ERROR: Could not unschedule scheduled constraint");
+
+    penalty_after - penalty_before
+}
+
+/// Calculates a lower bound on the change in the total penalty caused by scheduling a constraint
+/// in a slot, regardless of which other constraints are scheduled
+///
+/// Scheduling a constraint can never decrease the penalty of another constraint, and its own gap
+/// penalty is never negative, so only its own presence, allowed slots and preferred slots
+/// penalties are counted
+pub fn slot_cost_lower_bound(
+    schedule: &mut Schedule,
+    constraints: &ConstraintStore,
+    constraint: &Constraint,
+    slot: &Slot,
+    penalties_config: &PenaltiesConfig,
+) -> i64 {
+    let penalty_before = constraint.calculate_penalty(schedule, constraints, penalties_config);
+    schedule.schedule_constraint(constraint.id, constraint.duration, slot);
+    let penalty_after: u32 = constraint
+        .calculate_detailed_penalty(schedule, constraints, penalties_config)
+        .iter()
+        .filter(|(penalty, _)| !matches!(penalty, Penalty::Gap))
+        .map(|(_, value)| value)
+        .sum();
+    schedule
+        .unschedule_constraint(constraint.id)
+        .expect("LOGIC ERROR: Could not unschedule scheduled constraint");
+
+    penalty_after as i64 - penalty_before as i64
+}
@@ -0,0 +1,92 @@
+use std::{collections::BTreeSet, fmt};
+
+use rand::{Rng, rng, seq::IteratorRandom};
+
+use crate::{constraints::constraint_store::ConstraintStore, schedule::Schedule};
+
+/// Enum defining the different ruin operators of the large neighbourhood search
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RuinOperator {
+    Day,
+    TimeBand,
+    ConstraintType,
+}
+
+impl RuinOperator {
+    pub const ALL: [RuinOperator; 3] = [
+        RuinOperator::Day,
+        RuinOperator::TimeBand,
+        RuinOperator::ConstraintType,
+    ];
+}
+
+impl fmt::Display for RuinOperator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuinOperator::Day => write!(f, "Day"),
+            RuinOperator::TimeBand => write!(f, "Time band"),
+            RuinOperator::ConstraintType => write!(f, "Constraint type"),
+        }
+    }
+}
+
+/// Unschedules a part of the schedule chosen at random by the specified operator
+///
+/// * `Day` - Every constraint scheduled on a random day
+/// * `TimeBand` - Every constraint overlapping a random band of `time_band_width` slots on any day
+/// * `ConstraintType` - Every constraint of a random type
+///
+/// # Arguments
+/// * `ruin_operator` - The operator to ruin the schedule with
+/// * `schedule` - The schedule to ruin
+/// * `constraints` - The constraint store containing all constraints
+/// * `time_band_width` - The width (in slots) of the band ruined by the `TimeBand` operator
+///
+/// # Returns
+/// * `Vec<u32>` - The ids of the unscheduled constraints
+pub fn ruin(
+    ruin_operator: RuinOperator,
+    schedule: &mut Schedule,
+    constraints: &ConstraintStore,
+    time_band_width: u8,
+) -> Vec<u32> {
+    let ruined_constraints: BTreeSet<u32> = match ruin_operator {
+        RuinOperator::Day => {
+            let day = rng().random_range(0..schedule.grid.len());
+            schedule.grid[day].iter().flatten().copied().collect()
+        }
+        RuinOperator::TimeBand => {
+            let width = time_band_width.clamp(1, 48) as usize;
+            let start = rng().random_range(0..=(48 - width));
+            schedule
+                .grid
+                .iter()
+                .flat_map(|day| day[start..start + width].iter().flatten().copied())
+                .collect()
+        }
+        RuinOperator::ConstraintType => {
+            let Some(constraint_type) = constraints
+                .into_iter()
+                .map(|constraint| &constraint.name)
+                .choose(&mut rng())
+            else {
+                return Vec::new();
+            };
+
+            constraints
+                .get_constraint_ids_of_type(constraint_type)
+                .into_iter()
+                .filter(|constraint_id| schedule.is_constraint_scheduled(*constraint_id))
+                .collect()
+        }
+    };
+
+    ruined_constraints
+        .into_iter()
+        .inspect(|constraint_id| {
+            schedule
+                .unschedule_constraint(*constraint_id)
+                .expect("LOGIC ERROR: Could not unschedule scheduled constraint");
+        })
+        .collect()
+}
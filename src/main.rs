@@ -1,61 +1,117 @@
-use core::panic;
+use std::{path::Path, process::ExitCode};
 
 use crate::{
+    cli::{Cli, Command, commands},
     config::Config,
-    constraints::{
-        constraint_store::{ConstraintStore, load_constraint_store_from_file},
-        penalties::print_penalty_report,
-    },
-    global_search::run_global_search,
+    constraints::constraint_store::{ConstraintStore, load_constraint_store_from_files},
+    report::set_report_output,
+    stop_condition::register_interrupt_handler,
 };
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
 use env_logger;
-use log::{error, info};
+use log::info;
 
+mod alternatives;
+mod batch;
+mod cli;
 mod config;
 mod constraints;
+mod exact_search;
+mod explanation;
+mod genetic_algorithm;
 mod global_search;
 mod hill_climber;
+mod large_neighbourhood_search;
+mod multi_objective;
+mod presolve;
 mod random;
+mod report;
 mod schedule;
+mod schema;
+mod scoring;
 mod stats;
+mod stop_condition;
+mod tui;
+mod tuning;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     env_logger::init();
-    let config = Config::new()?;
-    info!("{:?}", config.optimisation_strategy_config);
-
-    let mut constraints: ConstraintStore =
-        load_constraint_store_from_file(config.constraint_file_path)
-            .expect("Could not load constraints from file. Please ensure the file exists");
+    let cli = Cli::parse();
+    if let Some(Command::Schema { kind, output }) = &cli.command {
+        commands::schema(*kind, output.as_deref())?;
+        return Ok(ExitCode::SUCCESS);
+    }
 
-    let (schedule, total_incurred_penalty, stats) = run_global_search(
-        &mut constraints,
-        config.iterations,
-        config.initial_temperature,
-        config.cooling_factor,
-        config.number_of_global_searches,
-        config.random_seed,
-        &config.penalties_config,
-        &config.optimisation_strategy_config
-    )
-    .unwrap_or_else(|error| {
-        error!("{}", error);
-        panic!();
-    });
+    let config_path = match &cli.config {
+        Some(config_path) if !config_path.exists() => {
+            bail!("The configuration file {:?} does not exist", config_path)
+        }
+        Some(config_path) => config_path.as_path(),
+        None => Path::new("config.toml"),
+    };
+    let config = Config::new(
+        config_path,
+        cli.profile.as_deref(),
+        &cli.config_overrides(),
+    )?;
+    info!("{:?}", config.optimisation_strategy_config);
+    register_interrupt_handler()?;
+    set_report_output(config.report_config.output_path.as_deref())
+        .map_err(|error| anyhow!("Could not open the report output: {}", error))?;
 
-    schedule
-        .export_to_csv(config.output_path, &constraints)
-        .expect("Could not export to csv");
+    let all_constraints: ConstraintStore =
+        load_constraint_store_from_files(&config.constraint_file_paths()).map_err(|error| {
+            anyhow!(
+                "Could not load constraints from {:?}: {}",
+                config.constraint_file_path,
+                error
+            )
+        })?;
+    let filter = config
+        .filter_config
+        .filter()
+        .expect("LOGIC ERROR: The filter is validated with the configuration");
+    let constraints = all_constraints.filter(&filter);
+    if !filter.is_empty() {
+        if constraints.is_empty() {
+            bail!("No constraints are left after filtering, check the selectors of filter_config");
+        }
+        info!(
+            "Filtered the constraints down to {} of {}",
+            constraints.len(),
+            all_constraints.len()
+        );
+    }
 
-    stats.generate_optimisation_report();
-    constraints.print_schedule_report(&schedule, total_incurred_penalty);
-    print_penalty_report(
-        &constraints,
-        &schedule,
-        total_incurred_penalty,
-        &config.penalties_config,
-    );
+    let outcome = match &cli.command {
+        None | Some(Command::Solve { .. }) => commands::solve(&constraints, &config)?,
+        Some(Command::Validate) => commands::validate(&constraints, &config),
+        Some(Command::Check { schedule }) => commands::check(&constraints, &config, schedule)?,
+        Some(Command::Render { schedule }) => {
+            commands::render(&constraints, &config, schedule)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Edit { schedule }) => {
+            commands::edit(&constraints, &config, schedule.as_deref())?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Diff { first, second }) => {
+            commands::diff(&constraints, &config, first, second)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Tune) => {
+            commands::tune(&constraints, &config)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Batch) => {
+            commands::batch(&constraints, &config)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Schema { .. }) => {
+            unreachable!("LOGIC ERROR: The schema is printed before loading")
+        }
+    };
 
-    Ok(())
+    Ok(outcome.exit_code())
 }
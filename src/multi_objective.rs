@@ -0,0 +1,340 @@
+pub mod pareto_archive;
+
+use std::{collections::HashMap, error::Error};
+
+use csv::Writer;
+use log::{debug, error, info};
+use rand::random;
+use rayon::prelude::*;
+
+use crate::{
+    alternatives::AlternativeArchive,
+    config::Config,
+    constraints::{
+        constraint_store::ConstraintStore, penalties::calculate_detailed_penalties,
+        penalty::Penalty,
+    },
+    global_search::{GlobalSearchError, HillClimbResult},
+    hill_climber::{
+        cooling::Cooling,
+        make_small_change::evolve_schedule,
+        strategy_selection::{StrategyOutcome, StrategySelector},
+    },
+    schedule::Schedule,
+    stats::OptimisationStats,
+    stop_condition::StopCondition,
+};
+use pareto_archive::{ParetoArchive, ParetoSolution};
+
+/// Runs `number_of_global_searches` Pareto annealing runs in parallel, keeping the schedules that
+/// are not dominated on the objectives chosen in the multi-objective configuration
+///
+/// Each run anneals a weighted sum of the objectives with its own randomly drawn weights (the first
+/// run weighs every objective equally), so that the runs spread out along the front. Every
+/// schedule accepted by a run is offered to the Pareto archive of the run, and the archives of all
+/// runs are merged at the end
+///
+/// # Arguments
+/// * `constraints` (&ConstraintStore) - The constraint store containing the constraints to
+///   schedule
+/// * `config` (&Config) - The configuration of the search
+/// * `stop_condition` (&StopCondition) - The stop condition of the whole global search
+///
+/// # Returns
+/// * (Schedule, u32, OptimisationStats) - The schedule of the front with the lowest total penalty,
+///   its penalty and the statistics of all runs, including the Pareto front
+/// * GlobalSearchError - If all of the runs failed
+pub fn run_multi_objective_search(
+    constraints: &ConstraintStore,
+    config: &Config,
+    stop_condition: &StopCondition,
+) -> Result<HillClimbResult, GlobalSearchError> {
+    let multi_objective_config = &config.multi_objective_config;
+    info!(
+        "Running multi-objective search over {:?}",
+        multi_objective_config.objectives
+    );
+
+    let results: Vec<(ParetoArchive, OptimisationStats)> = (0..config.number_of_global_searches)
+        .into_par_iter()
+        .filter_map(|i| {
+            let initial_schedule = Schedule::random(constraints, config.random_seed, Some(i));
+            let weights = match i {
+                0 => vec![1.0; multi_objective_config.objectives.len()],
+                _ => draw_objective_weights(multi_objective_config.objectives.len()),
+            };
+            debug!(
+                "Running Pareto annealing with objective weights {:?}",
+                weights
+            );
+
+            run_pareto_annealing(
+                constraints,
+                initial_schedule,
+                config,
+                &weights,
+                &stop_condition.for_run(config.time_limit_config.run_limit()),
+            )
+            .inspect_err(|error| error!("Error occurred executing Pareto annealing: {}", error))
+            .ok()
+        })
+        .collect();
+
+    let mut archive = ParetoArchive::new(multi_objective_config.archive_size as usize);
+    let mut stats = OptimisationStats {
+        alternative_archive: AlternativeArchive::new(&config.alternatives_config),
+        ..OptimisationStats::default()
+    };
+    for (run_archive, run_stats) in results {
+        archive.merge(run_archive);
+        stats.merge(&run_stats);
+        stats.stop_reason = stats.stop_reason.or(run_stats.stop_reason);
+    }
+
+    let pareto_front = archive.into_solutions();
+    let Some(best_solution) = pareto_front.first() else {
+        return Err(GlobalSearchError::NoResults);
+    };
+    let best_schedule = best_solution.schedule.clone();
+    let best_total_penalty = best_solution.total_penalty;
+    info!(
+        "Multi-objective search complete. Identified {} schedules on the Pareto front",
+        pareto_front.len()
+    );
+
+    stats.pareto_front = pareto_front;
+    Ok((best_schedule, best_total_penalty, stats))
+}
+
+/// Draws random positive weights for the objectives, scaled to sum to the number of objectives
+///
+/// NOTE: Scaling the weights keeps the weighted sum on the same scale as the total penalty, so the
+/// configured temperatures behave the same as in the other search modes
+fn draw_objective_weights(objective_count: usize) -> Vec<f32> {
+    let weights: Vec<f32> = (0..objective_count)
+        .map(|_| random::<f32>().max(0.05))
+        .collect();
+    let total_weight: f32 = weights.iter().sum();
+
+    weights
+        .iter()
+        .map(|weight| weight / total_weight * objective_count as f32)
+        .collect()
+}
+
+/// Runs a single Pareto annealing run
+///
+/// Changes are made with the optimisation strategies of the hill climber, and are accepted with
+/// the Metropolis criterion on the weighted sum of the objectives
+///
+/// # Arguments
+/// * `constraints` - The constraints store containing all the constraints to satisfy
+/// * `initial_schedule` - The schedule to start the run from
+/// * `config` - The configuration of the search
+/// * `weights` - The weight of each objective, in the order of the configured objectives
+/// * `stop_condition` - The wall-clock/interrupt based condition checked before every iteration
+///
+/// # Returns
+/// * (ParetoArchive, OptimisationStats) - The non-dominated schedules found by the run and the
+///   statistics of the run
+fn run_pareto_annealing(
+    constraints: &ConstraintStore,
+    initial_schedule: Schedule,
+    config: &Config,
+    weights: &[f32],
+    stop_condition: &StopCondition,
+) -> Result<(ParetoArchive, OptimisationStats), Box<dyn Error>> {
+    let objectives = &config.multi_objective_config.objectives;
+    let mut archive = ParetoArchive::new(config.multi_objective_config.archive_size as usize);
+    let mut stats = OptimisationStats {
+        alternative_archive: AlternativeArchive::new(&config.alternatives_config),
+        ..OptimisationStats::default()
+    };
+    let mut strategy_selector = StrategySelector::new(&config.optimisation_strategy_config);
+    let mut cooling = Cooling::new(
+        &config.annealing_config,
+        config.initial_temperature,
+        config.cooling_factor,
+        config.iterations,
+    );
+
+    let mut schedule = initial_schedule;
+    let (mut penalties, mut scores, mut total_penalty) =
+        evaluate_objectives(constraints, &schedule, config, objectives);
+    let mut weighted_score = weigh_scores(&scores, weights);
+    archive.offer(ParetoSolution {
+        schedule: schedule.clone(),
+        scores: scores.clone(),
+        total_penalty,
+    });
+
+    for iteration in 0..config.iterations {
+        if total_penalty == 0 {
+            break;
+        }
+
+        if let Some(stop_reason) = stop_condition.check() {
+            info!(
+                "Stopping run at iteration {:?} ({})",
+                iteration, stop_reason
+            );
+            stats.stop_reason = Some(stop_reason);
+            break;
+        }
+
+        cooling.cool();
+        let Some(changes) = evolve_schedule(
+            constraints,
+            &penalties,
+            &mut schedule,
+            &mut stats,
+            &mut strategy_selector,
+        )?
+        else {
+            strategy_selector.record_outcome(StrategyOutcome::Rejected);
+            continue;
+        };
+
+        let (new_penalties, new_scores, new_total_penalty) =
+            evaluate_objectives(constraints, &schedule, config, objectives);
+        let new_weighted_score = weigh_scores(&new_scores, weights);
+        let delta = new_weighted_score - weighted_score;
+        let accepted = delta <= 0.0
+            || random::<f32>() < (-delta / cooling.temperature().max(f32::EPSILON)).exp();
+
+        strategy_selector.record_outcome(if !accepted {
+            StrategyOutcome::Rejected
+        } else if delta < 0.0 {
+            StrategyOutcome::Improved
+        } else {
+            StrategyOutcome::Accepted
+        });
+        if !accepted {
+            stats.revert_count += 1;
+            changes
+                .iter()
+                .rev()
+                .for_each(|change| change.revert_change(&mut schedule));
+            continue;
+        }
+
+        penalties = new_penalties;
+        scores = new_scores;
+        total_penalty = new_total_penalty;
+        weighted_score = new_weighted_score;
+
+        stats.alternative_archive.offer(&schedule, total_penalty);
+        if archive.accepts(&scores) {
+            archive.offer(ParetoSolution {
+                schedule: schedule.clone(),
+                scores: scores.clone(),
+                total_penalty,
+            });
+        }
+    }
+
+    Ok((archive, stats))
+}
+
+/// Evaluates the penalties of a schedule on each objective
+///
+/// # Arguments
+/// * `constraints` - The constraints store containing all the constraints to satisfy
+/// * `schedule` - The schedule to evaluate
+/// * `config` - The configuration specifying the values used for penalties
+/// * `objectives` - The penalty types to score separately
+///
+/// # Returns
+/// * (HashMap<u32, u32>, Vec<(Penalty, u32)>, u32) - The penalty incurred by each constraint, the
+///   total of each objective penalty type and the total penalty over all penalty types
+fn evaluate_objectives(
+    constraints: &ConstraintStore,
+    schedule: &Schedule,
+    config: &Config,
+    objectives: &[Penalty],
+) -> (HashMap<u32, u32>, Vec<(Penalty, u32)>, u32) {
+    let detailed_penalties =
+        calculate_detailed_penalties(constraints, schedule, &config.penalties_config);
+
+    let mut scores: Vec<(Penalty, u32)> =
+        objectives.iter().map(|objective| (*objective, 0)).collect();
+    let mut penalties: HashMap<u32, u32> = HashMap::new();
+    let mut total_penalty = 0;
+
+    for (constraint_id, constraint_penalties) in &detailed_penalties {
+        let constraint_penalty: u32 = constraint_penalties.iter().map(|(_, value)| value).sum();
+        penalties.insert(*constraint_id, constraint_penalty);
+        total_penalty += constraint_penalty;
+
+        for (penalty, value) in constraint_penalties {
+            for (objective, score) in scores.iter_mut() {
+                if objective == penalty {
+                    *score += value;
+                }
+            }
+        }
+    }
+
+    (penalties, scores, total_penalty)
+}
+
+/// Calculates the weighted sum of the objective scores
+fn weigh_scores(scores: &[(Penalty, u32)], weights: &[f32]) -> f32 {
+    scores
+        .iter()
+        .zip(weights)
+        .map(|((_, score), weight)| *score as f32 * weight)
+        .sum()
+}
+
+/// Exports each schedule of the Pareto front to its own csv file next to the best schedule, along
+/// with a csv summary of the score of each schedule on each objective
+///
+/// The schedules are exported to `<output>_pareto_<n>.csv` and the summary to
+/// `<output>_pareto_front.csv`, where `<output>` is the output path without its extension
+///
+/// # Arguments
+/// * `pareto_front` - The schedules of the Pareto front
+/// * `output_path` - The path the best schedule is exported to
+/// * `constraints` - The constraint store containing all constraints
+pub fn export_pareto_front(
+    pareto_front: &[ParetoSolution],
+    output_path: &str,
+    constraints: &ConstraintStore,
+) -> Result<(), Box<dyn Error>> {
+    let output_stem = output_path
+        .strip_suffix(".csv")
+        .unwrap_or(output_path)
+        .to_string();
+
+    let summary_path = format!("{}_pareto_front.csv", output_stem);
+    info!("Exporting Pareto front to csv ({:?})", summary_path);
+    let mut csv_writer = Writer::from_path(summary_path)?;
+    if let Some(solution) = pareto_front.first() {
+        let mut header = vec!["Schedule".to_string()];
+        header.extend(
+            solution
+                .scores
+                .iter()
+                .map(|(penalty, _)| penalty.to_string()),
+        );
+        header.push("Total".to_string());
+        csv_writer.write_record(&header)?;
+    }
+
+    for (i, solution) in pareto_front.iter().enumerate() {
+        let file_name = format!("{}_pareto_{}.csv", output_stem, i + 1);
+        solution
+            .schedule
+            .export_to_csv(file_name.clone(), constraints)?;
+
+        let mut record = vec![file_name];
+        record.extend(solution.scores.iter().map(|(_, score)| score.to_string()));
+        record.push(solution.total_penalty.to_string());
+        csv_writer.write_record(&record)?;
+    }
+
+    csv_writer.flush()?;
+    info!("Exported Pareto front to csv");
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use crate::{constraints::penalty::Penalty, schedule::Schedule};
+
+/// A schedule of the Pareto front with its score on each objective
+///
+/// * `schedule` - The schedule
+/// * `scores` - The total of each penalty type chosen as an objective
+/// * `total_penalty` - The total penalty of the schedule over all penalty types
+#[derive(Clone)]
+pub struct ParetoSolution {
+    pub schedule: Schedule,
+    pub scores: Vec<(Penalty, u32)>,
+    pub total_penalty: u32,
+}
+
+impl ParetoSolution {
+    /// Checks whether this solution scores no worse on every objective and better on at least
+    /// one objective than another solution
+    fn dominates(&self, other: &ParetoSolution) -> bool {
+        let mut is_better = false;
+        for ((_, score), (_, other_score)) in self.scores.iter().zip(&other.scores) {
+            if score > other_score {
+                return false;
+            }
+            is_better |= score < other_score;
+        }
+
+        is_better
+    }
+}
+
+/// An archive of the schedules not dominated by any other schedule offered to it
+pub struct ParetoArchive {
+    capacity: usize,
+    solutions: Vec<ParetoSolution>,
+}
+
+impl ParetoArchive {
+    /// Creates an empty archive
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of schedules to keep
+    pub fn new(capacity: usize) -> Self {
+        ParetoArchive {
+            capacity: capacity.max(1),
+            solutions: Vec::new(),
+        }
+    }
+
+    /// Offers a solution to the archive
+    ///
+    /// The solution is rejected if a solution in the archive dominates it or has the same scores
+    /// Otherwise it is added and the solutions it dominates are removed. When the archive is over
+    /// capacity, the solution in the most crowded part of the front is removed
+    ///
+    /// # Arguments
+    /// * `solution` - The solution to offer
+    ///
+    /// # Returns
+    /// * `bool` - Whether the solution was added to the archive
+    pub fn offer(&mut self, solution: ParetoSolution) -> bool {
+        if !self.accepts(&solution.scores) {
+            return false;
+        }
+
+        self.solutions
+            .retain(|archived| !solution.dominates(archived));
+        self.solutions.push(solution);
+        if self.solutions.len() > self.capacity {
+            self.remove_most_crowded();
+        }
+        true
+    }
+
+    /// Offers every solution of another archive to this archive
+    ///
+    /// # Arguments
+    /// * `other` - The archive to take the solutions from
+    pub fn merge(&mut self, other: ParetoArchive) {
+        for solution in other.solutions {
+            self.offer(solution);
+        }
+    }
+
+    /// Checks whether a solution with the specified scores would be added to the archive, without
+    /// building the solution
+    ///
+    /// # Arguments
+    /// * `scores` - The scores of the solution
+    pub fn accepts(&self, scores: &[(Penalty, u32)]) -> bool {
+        !self.solutions.iter().any(|archived| {
+            archived
+                .scores
+                .iter()
+                .zip(scores)
+                .all(|((_, archived_score), (_, score))| archived_score <= score)
+        })
+    }
+
+    /// Returns the solutions of the archive, ordered by total penalty
+    pub fn into_solutions(mut self) -> Vec<ParetoSolution> {
+        self.solutions.sort_by_key(|solution| {
+            (
+                solution.total_penalty,
+                solution
+                    .scores
+                    .iter()
+                    .map(|(_, score)| *score)
+                    .collect::<Vec<u32>>(),
+            )
+        });
+        self.solutions
+    }
+
+    /// Removes the solution with the smallest crowding distance (NSGA-II)
+    ///
+    /// The crowding distance of a solution is the sum over the objectives of the normalised
+    /// distance between its neighbours on that objective. The solutions at the extremes of an
+    /// objective are never removed
+    fn remove_most_crowded(&mut self) {
+        let solution_count = self.solutions.len();
+        let objective_count = self.solutions[0].scores.len();
+        let mut crowding_distances = vec![0.0; solution_count];
+
+        for objective in 0..objective_count {
+            let score = |i: usize| self.solutions[i].scores[objective].1 as f64;
+            let mut order: Vec<usize> = (0..solution_count).collect();
+            order.sort_by(|first, second| score(*first).total_cmp(&score(*second)));
+
+            let range = score(order[solution_count - 1]) - score(order[0]);
+            if range == 0.0 {
+                continue;
+            }
+
+            crowding_distances[order[0]] = f64::INFINITY;
+            crowding_distances[order[solution_count - 1]] = f64::INFINITY;
+
+            for window in order.windows(3) {
+                crowding_distances[window[1]] += (score(window[2]) - score(window[0])) / range;
+            }
+        }
+
+        if let Some(most_crowded) = (0..solution_count).min_by(|first, second| {
+            crowding_distances[*first].total_cmp(&crowding_distances[*second])
+        }) {
+            self.solutions.swap_remove(most_crowded);
+        }
+    }
+}
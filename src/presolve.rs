@@ -0,0 +1,359 @@
+use core::fmt;
+use std::{collections::HashSet, error::Error};
+
+use crate::{
+    config::PenaltiesConfig,
+    constraints::{
+        Constraint, ConstraintPriority, constraint_store::ConstraintStore, penalty::Penalty,
+    },
+    exact_search::calculate_lower_bound,
+    report::report,
+    schedule::Slot,
+};
+
+/// The number of slots in a week
+const AVAILABLE_SLOTS: u32 = 7 * 48;
+
+/// A reason why the constraints can not all be satisfied, detected before optimisation
+pub enum PresolveIssue {
+    /// The constraints with a presence penalty need more slots than there are in a week
+    Overcommitted { required_slots: u32 },
+    /// A high priority constraint with an allowed slots penalty has no allowed slot it fits in
+    NoFittingAllowedSlot { constraint_id: u32, name: String },
+    /// Every allowed slot of a high priority constraint overlaps a pinned constraint (a high
+    /// priority constraint with a single allowed slot)
+    AllowedSlotsBlocked {
+        constraint_id: u32,
+        name: String,
+        blocking_constraint_ids: Vec<u32>,
+    },
+}
+
+impl fmt::Display for PresolveIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PresolveIssue::Overcommitted { required_slots } => write!(
+                f,
+                "The constraints need {} slots but a week only has {}",
+                required_slots, AVAILABLE_SLOTS
+            ),
+            PresolveIssue::NoFittingAllowedSlot {
+                constraint_id,
+                name,
+            } => write!(
+                f,
+                "[{}:{}] None of the allowed slots fit the duration of the constraint",
+                constraint_id,
+                name.to_uppercase()
+            ),
+            PresolveIssue::AllowedSlotsBlocked {
+                constraint_id,
+                name,
+                blocking_constraint_ids,
+            } => write!(
+                f,
+                "[{}:{}] Every allowed slot clashes with a pinned constraint ({:?})",
+                constraint_id,
+                name.to_uppercase(),
+                blocking_constraint_ids
+            ),
+        }
+    }
+}
+
+/// The result of the analysis run before optimisation
+///
+/// * `lower_bound` - A lower bound on the total penalty of every schedule
+/// * `issues` - The reasons why the constraints can not all be satisfied
+pub struct PresolveReport {
+    pub lower_bound: u32,
+    pub issues: Vec<PresolveIssue>,
+}
+
+impl PresolveReport {
+    /// Checks whether any reason was found for the constraints not to be satisfiable
+    pub fn is_infeasible(&self) -> bool {
+        !self.issues.is_empty()
+    }
+
+    /// Writes the report to the report output
+    pub fn print(&self) {
+        report!("=== PRESOLVE REPORT ===");
+        report!("LOWER BOUND ON TOTAL PENALTY: {}", self.lower_bound);
+        if self.issues.is_empty() {
+            report!("No infeasibilities detected");
+        }
+        for issue in &self.issues {
+            report!("  └─ {}", issue);
+        }
+        report!("=======================");
+    }
+}
+
+#[derive(Debug)]
+pub enum PresolveError {
+    Infeasible(usize),
+}
+
+impl fmt::Display for PresolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PresolveError::Infeasible(issue_count) => write!(
+                f,
+                "The constraints are infeasible ({} issues found). Disable strict presolve to \
+                 optimise anyway",
+                issue_count
+            ),
+        }
+    }
+}
+
+impl Error for PresolveError {}
+
+/// Analyses the constraints before optimisation, calculating a lower bound on the total penalty
+/// and detecting sets of constraints that can not all be satisfied
+///
+/// The lower bound is the largest of
+/// * The bound of the exact search before any constraint is assigned
+/// * The cheapest presence penalties that have to be paid to fit the constraints into a week
+/// * The cheapest penalties that have to be paid to resolve the clashes with pinned constraints
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing all constraints
+/// * `penalties_config` - The configuration specifying values used for penalties
+///
+/// # Returns
+/// * `PresolveReport` - The lower bound and the detected issues
+pub fn presolve(
+    constraints: &ConstraintStore,
+    penalties_config: &PenaltiesConfig,
+) -> PresolveReport {
+    let mut issues = Vec::new();
+
+    let required_slots: u32 = constraints
+        .into_iter()
+        .filter(|constraint| constraint.penalties.contains(&Penalty::Presence))
+        .map(|constraint| constraint.duration as u32)
+        .sum();
+    if required_slots > AVAILABLE_SLOTS {
+        issues.push(PresolveIssue::Overcommitted { required_slots });
+    }
+
+    let fitting_allowed_slots = |constraint: &Constraint| -> Vec<Slot> {
+        constraint
+            .allowed_slots
+            .iter()
+            .flatten()
+            .filter(|slot| slot.day < 7 && slot.window as u32 + constraint.duration as u32 <= 48)
+            .cloned()
+            .collect()
+    };
+
+    let is_malformed = constraints.into_iter().any(|constraint| {
+        (constraint.penalties.contains(&Penalty::AllowedSlots)
+            && constraint.allowed_slots.is_none())
+            || (constraint.penalties.contains(&Penalty::PreferredSlots)
+                && constraint.preferred_slots.is_none())
+            || (constraint.penalties.contains(&Penalty::Gap) && constraint.gap.is_none())
+    });
+
+    let pinned: Vec<(&Constraint, Slot)> = constraints
+        .into_iter()
+        .filter(|constraint| {
+            constraint.priority == ConstraintPriority::High
+                && constraint.penalties.contains(&Penalty::Presence)
+                && constraint.penalties.contains(&Penalty::AllowedSlots)
+        })
+        .filter_map(|constraint| match fitting_allowed_slots(constraint).as_slice() {
+            [slot] => Some((constraint, slot.clone())),
+            _ => None,
+        })
+        .collect();
+
+    let mut blocked: Vec<(&Constraint, Vec<u32>)> = Vec::new();
+    for constraint in constraints.into_iter().filter(|constraint| {
+        constraint.priority == ConstraintPriority::High
+            && constraint.penalties.contains(&Penalty::AllowedSlots)
+    }) {
+        let allowed_slots = fitting_allowed_slots(constraint);
+        if allowed_slots.is_empty() {
+            issues.push(PresolveIssue::NoFittingAllowedSlot {
+                constraint_id: constraint.id,
+                name: constraint.name.clone(),
+            });
+            continue;
+        }
+
+        let mut blocking_constraint_ids = Vec::new();
+        let is_blocked = allowed_slots.iter().all(|slot| {
+            let blocking: Vec<u32> = pinned
+                .iter()
+                .filter(|(pinned_constraint, pinned_slot)| {
+                    pinned_constraint.id != constraint.id
+                        && overlaps(
+                            slot,
+                            constraint.duration,
+                            pinned_slot,
+                            pinned_constraint.duration,
+                        )
+                })
+                .map(|(pinned_constraint, _)| pinned_constraint.id)
+                .collect();
+            blocking_constraint_ids.extend(&blocking);
+            !blocking.is_empty()
+        });
+
+        if is_blocked {
+            blocking_constraint_ids.sort_unstable();
+            blocking_constraint_ids.dedup();
+            issues.push(PresolveIssue::AllowedSlotsBlocked {
+                constraint_id: constraint.id,
+                name: constraint.name.clone(),
+                blocking_constraint_ids: blocking_constraint_ids.clone(),
+            });
+            blocked.push((constraint, blocking_constraint_ids));
+        }
+    }
+
+    // NOTE: The exact search bound evaluates every penalty of every constraint, which is not
+    // possible when a penalty is missing the field it is based on
+    let search_lower_bound = match is_malformed {
+        true => 0,
+        false => calculate_lower_bound(constraints, penalties_config),
+    };
+    let lower_bound = search_lower_bound
+        .max(overcommitment_lower_bound(
+            constraints,
+            required_slots,
+            penalties_config,
+        ))
+        .max(clash_lower_bound(constraints, &blocked, penalties_config));
+
+    PresolveReport {
+        lower_bound,
+        issues,
+    }
+}
+
+/// Checks whether two constraints scheduled in the specified slots overlap
+fn overlaps(
+    first_slot: &Slot,
+    first_duration: u8,
+    second_slot: &Slot,
+    second_duration: u8,
+) -> bool {
+    first_slot.day == second_slot.day
+        && first_slot.window < second_slot.window + second_duration
+        && second_slot.window < first_slot.window + first_duration
+}
+
+/// Calculates a lower bound on the presence penalties that have to be paid for the constraints
+/// with a presence penalty to fit into a week
+///
+/// The constraints with the lowest presence penalty per slot are left unscheduled first, allowing
+/// a constraint to be left partially unscheduled
+fn overcommitment_lower_bound(
+    constraints: &ConstraintStore,
+    required_slots: u32,
+    penalties_config: &PenaltiesConfig,
+) -> u32 {
+    let Some(mut excess_slots) = required_slots
+        .checked_sub(AVAILABLE_SLOTS)
+        .filter(|excess_slots| *excess_slots > 0)
+    else {
+        return 0;
+    };
+
+    let mut presence_penalties: Vec<(u32, u32)> = constraints
+        .into_iter()
+        .filter(|constraint| constraint.penalties.contains(&Penalty::Presence))
+        .map(|constraint| {
+            (
+                presence_penalty(constraint, penalties_config),
+                constraint.duration as u32,
+            )
+        })
+        .filter(|(_, duration)| *duration > 0)
+        .collect();
+    presence_penalties.sort_by(|(first_penalty, first_length), (second_penalty, second_length)| {
+        let first_per_slot = *first_penalty as f64 / *first_length as f64;
+        let second_per_slot = *second_penalty as f64 / *second_length as f64;
+        first_per_slot.total_cmp(&second_per_slot)
+    });
+
+    let mut lower_bound = 0.0;
+    for (penalty, duration) in presence_penalties {
+        let unscheduled_slots = duration.min(excess_slots);
+        lower_bound += penalty as f64 * unscheduled_slots as f64 / duration as f64;
+        excess_slots -= unscheduled_slots;
+        if excess_slots == 0 {
+            break;
+        }
+    }
+
+    // NOTE: Rounded down (after allowing for floating point error) so the bound stays valid
+    (lower_bound + 1e-9).floor() as u32
+}
+
+/// Calculates a lower bound on the penalties that have to be paid to resolve the clashes with
+/// pinned constraints
+///
+/// For each clash, either the blocked constraint or one of the pinned constraints blocking it
+/// has to leave its allowed slot. Only clashes that do not share constraints are counted, so no
+/// penalty is counted twice
+fn clash_lower_bound(
+    constraints: &ConstraintStore,
+    blocked: &[(&Constraint, Vec<u32>)],
+    penalties_config: &PenaltiesConfig,
+) -> u32 {
+    let mut counted_constraint_ids: HashSet<u32> = HashSet::new();
+    let mut lower_bound = 0;
+
+    for (constraint, blocking_constraint_ids) in blocked {
+        let involved: Vec<&Constraint> = std::iter::once(*constraint)
+            .chain(
+                blocking_constraint_ids
+                    .iter()
+                    .filter_map(|constraint_id| constraints.get_constraint(*constraint_id)),
+            )
+            .collect();
+        if involved
+            .iter()
+            .any(|constraint| counted_constraint_ids.contains(&constraint.id))
+        {
+            continue;
+        }
+
+        counted_constraint_ids.extend(involved.iter().map(|constraint| constraint.id));
+        lower_bound += involved
+            .iter()
+            .map(|constraint| deviation_penalty(constraint, penalties_config))
+            .min()
+            .unwrap_or(0);
+    }
+
+    lower_bound
+}
+
+/// Returns the presence penalty of a constraint
+fn presence_penalty(constraint: &Constraint, penalties_config: &PenaltiesConfig) -> u32 {
+    match constraint.priority {
+        ConstraintPriority::High => penalties_config.presence_high,
+        ConstraintPriority::Low => penalties_config.presence_low,
+    }
+}
+
+/// Returns the lowest penalty a constraint incurs when it is not in one of its allowed slots
+fn deviation_penalty(constraint: &Constraint, penalties_config: &PenaltiesConfig) -> u32 {
+    if !constraint.penalties.contains(&Penalty::Presence)
+        || !constraint.penalties.contains(&Penalty::AllowedSlots)
+    {
+        return 0;
+    }
+
+    let allowed_slots_penalty = match constraint.priority {
+        ConstraintPriority::High => penalties_config.allowed_slots_high,
+        ConstraintPriority::Low => penalties_config.allowed_slots_low,
+    };
+    presence_penalty(constraint, penalties_config).min(allowed_slots_penalty)
+}
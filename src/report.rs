@@ -0,0 +1,60 @@
+use std::{
+    fmt,
+    fs::File,
+    io::{self, IsTerminal, Write},
+    sync::Mutex,
+};
+
+use log::warn;
+
+/// Where the reports are written to. Standard out until `set_report_output` is called
+static REPORT_OUTPUT: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+/// Writes a line to the report output, regardless of the log level
+///
+/// Takes the same arguments as `println!`
+macro_rules! report {
+    ($($arg:tt)*) => {
+        $crate::report::write_report_line(format_args!($($arg)*))
+    };
+}
+pub(crate) use report;
+
+/// Sends the reports to a file instead of standard out
+///
+/// # Arguments
+/// * `output_path` - The file to write the reports to, replacing its contents. Standard out when
+///   `None`
+pub fn set_report_output(output_path: Option<&str>) -> io::Result<()> {
+    let output: Option<Box<dyn Write + Send>> = match output_path {
+        Some(output_path) => Some(Box::new(File::create(output_path)?)),
+        None => None,
+    };
+    *REPORT_OUTPUT
+        .lock()
+        .expect("LOGIC ERROR: The report output is never poisoned") = output;
+    Ok(())
+}
+
+/// Returns whether the reports are written to a terminal
+pub fn is_report_output_terminal() -> bool {
+    REPORT_OUTPUT
+        .lock()
+        .expect("LOGIC ERROR: The report output is never poisoned")
+        .is_none()
+        && io::stdout().is_terminal()
+}
+
+/// Writes a line to the report output. Use the `report!` macro instead
+pub fn write_report_line(line: fmt::Arguments) {
+    let mut report_output = REPORT_OUTPUT
+        .lock()
+        .expect("LOGIC ERROR: The report output is never poisoned");
+    let result = match report_output.as_mut() {
+        Some(output) => writeln!(output, "{}", line),
+        None => writeln!(io::stdout(), "{}", line),
+    };
+    if let Err(error) = result {
+        warn!("Could not write the report: {}", error);
+    }
+}
@@ -1,12 +1,15 @@
 pub mod errors;
+pub mod grid_renderer;
 
-use csv::Writer;
+use csv::{Reader, Writer};
 use log::info;
 use rand::seq::IteratorRandom;
 use rand::{Rng, rng};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
+use std::fmt;
 use std::{array, usize};
 
 use crate::constraints::constraint_store::ConstraintStore;
@@ -14,12 +17,29 @@ use crate::hill_climber::make_small_change::SchedulableSlots;
 use crate::random::generate_sobol_indices;
 use crate::schedule::errors::ScheduleError;
 
-#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct Slot {
+    #[schemars(range(max = 6))]
     pub day: u8,
+    #[schemars(range(max = 47))]
     pub window: u8,
 }
 
+pub const DAY_NAMES: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+impl fmt::Display for Slot {
+    /// Formats the slot as the day and the time of day it starts at (e.g. Mon 09:30)
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {:02}:{:02}",
+            DAY_NAMES.get(self.day as usize).unwrap_or(&"???"),
+            self.window / 2,
+            (self.window % 2) * 30
+        )
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct Schedule {
     #[serde(with = "serde_arrays")]
@@ -85,6 +105,36 @@ impl Schedule {
         schedule
     }
 
+    /// Moves randomly chosen scheduled constraints to other free compatible slots
+    ///
+    /// A constraint for which no other free compatible slot exists stays where it is
+    ///
+    /// # Arguments
+    /// * `constraint_store` - Constraint store with all the constraints in the schedule
+    /// * `number_of_moves` - The number of constraints to move
+    pub fn perturb(&mut self, constraint_store: &ConstraintStore, number_of_moves: u32) {
+        let scheduled_constraints = constraint_store
+            .into_iter()
+            .filter(|constraint| self.is_constraint_scheduled(constraint.id))
+            .choose_multiple(&mut rng(), number_of_moves as usize);
+
+        for constraint in scheduled_constraints {
+            let previous_slot = self
+                .unschedule_constraint(constraint.id)
+                .expect("LOGIC ERROR: Could not unschedule scheduled constraint");
+            let slot = self
+                .get_free_slot_for_constraint(
+                    constraint.duration,
+                    &SchedulableSlots {
+                        allowed_slots: constraint.allowed_slots.clone(),
+                        preferred_slots: constraint.preferred_slots.clone(),
+                    },
+                )
+                .unwrap_or(previous_slot);
+            self.schedule_constraint(constraint.id, constraint.duration, &slot);
+        }
+    }
+
     /// Finds a free compatible slot to schedule a constraint
     ///
     /// /// TODO: Add a way to make allowed slots and preferred slots work togeather,
@@ -280,6 +330,33 @@ impl Schedule {
         }
     }
 
+    /// Counts the constraints placed differently in two schedules
+    ///
+    /// A constraint is placed differently if it is scheduled in different slots, or scheduled in
+    /// only one of the schedules
+    ///
+    /// # Arguments
+    /// * `other` - The schedule to compare with
+    ///
+    /// # Returns
+    /// * `u32` - The number of constraints placed differently
+    pub fn placement_distance(&self, other: &Schedule) -> u32 {
+        let moved_count = self
+            .scheduled_constraints
+            .iter()
+            .filter(|(constraint_id, (slot, _))| {
+                other.get_scheduled_slot_for_constraint(**constraint_id) != Some(slot)
+            })
+            .count();
+        let added_count = other
+            .scheduled_constraints
+            .keys()
+            .filter(|constraint_id| !self.scheduled_constraints.contains_key(constraint_id))
+            .count();
+
+        (moved_count + added_count) as u32
+    }
+
     /// Returns `true` if a range of slots is either empty or occupied
     /// exclusively by the specified constraint.
     ///
@@ -421,4 +498,99 @@ impl Schedule {
         info!("Exported schedule to csv");
         Ok(())
     }
+
+    /// Imports a schedule exported with `export_to_csv`
+    ///
+    /// Each constraint is scheduled at the first window it occupies
+    ///
+    /// # Arguments
+    /// * `file_name` - The path of the csv file
+    /// * `constraint_store` - The constraint store containing the constraints of the schedule
+    ///
+    /// # Returns
+    /// * `Schedule` - The imported schedule
+    /// * `Box<dyn Error>` - If the file could not be read, or does not match the constraints
+    pub fn import_from_csv(
+        file_name: &str,
+        constraint_store: &ConstraintStore,
+    ) -> Result<Schedule, Box<dyn Error>> {
+        info!("Importing schedule from csv ({:?})", file_name);
+        let mut csv_reader = Reader::from_path(file_name)?;
+        let mut grid: [[Option<u32>; 48]; 7] = array::from_fn(|_| array::from_fn(|_| None));
+
+        let mut window_count = 0;
+        for (window, record) in csv_reader.records().enumerate() {
+            let record = record?;
+            if window >= 48 || record.len() != 7 {
+                return Err(ScheduleError::InvalidCsv(
+                    "Expected 48 rows of 7 days".to_string(),
+                )
+                .into());
+            }
+
+            for (day, cell) in record.iter().enumerate() {
+                if cell == "Free" {
+                    continue;
+                }
+
+                // NOTE: Cells are formatted as `<name>:<id>`, and names may contain colons
+                let constraint_id = cell
+                    .rsplit_once(':')
+                    .and_then(|(_, constraint_id)| constraint_id.parse::<u32>().ok())
+                    .ok_or_else(|| ScheduleError::InvalidCsv(format!("Invalid cell {:?}", cell)))?;
+                grid[day][window] = Some(constraint_id);
+            }
+            window_count += 1;
+        }
+
+        if window_count != 48 {
+            return Err(ScheduleError::InvalidCsv("Expected 48 rows of 7 days".to_string()).into());
+        }
+
+        let mut schedule = Schedule::new();
+        for (day, day_windows) in grid.iter().enumerate() {
+            for (window, cell) in day_windows.iter().enumerate() {
+                let Some(constraint_id) = *cell else {
+                    continue;
+                };
+                if schedule.is_constraint_scheduled(constraint_id) {
+                    continue;
+                }
+
+                let constraint = constraint_store
+                    .get_constraint(constraint_id)
+                    .ok_or(ScheduleError::UnknownConstraint(constraint_id))?;
+                let occupied = day_windows[window..]
+                    .iter()
+                    .take_while(|cell| **cell == Some(constraint_id))
+                    .count();
+                if occupied != constraint.duration as usize {
+                    return Err(ScheduleError::InvalidCsv(format!(
+                        "Constraint {} occupies {} consecutive windows but lasts {}",
+                        constraint_id, occupied, constraint.duration
+                    ))
+                    .into());
+                }
+
+                schedule.schedule_constraint(
+                    constraint_id,
+                    constraint.duration,
+                    &Slot {
+                        day: day as u8,
+                        window: window as u8,
+                    },
+                );
+            }
+        }
+
+        if schedule.grid != grid {
+            return Err(ScheduleError::InvalidCsv(
+                "A constraint is placed more than once".to_string(),
+            )
+            .into());
+        }
+
+        info!("Imported schedule from csv");
+        Ok(schedule)
+    }
 }
@@ -3,6 +3,8 @@ use std::{error::Error, fmt};
 #[derive(Debug)]
 pub enum ScheduleError {
     ConstraintNotScheduled(u32),
+    InvalidCsv(String),
+    UnknownConstraint(u32),
 }
 
 impl fmt::Display for ScheduleError {
@@ -11,6 +13,14 @@ impl fmt::Display for ScheduleError {
             ScheduleError::ConstraintNotScheduled(constraint_id) => {
                 write!(f, "Constraint '{}' is not scheduled", constraint_id)
             }
+            ScheduleError::InvalidCsv(msg) => write!(f, "Invalid schedule csv: {}", msg),
+            ScheduleError::UnknownConstraint(constraint_id) => {
+                write!(
+                    f,
+                    "Constraint '{}' is not in the constraint file",
+                    constraint_id
+                )
+            }
         }
     }
 }
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+use crossterm::style::{Color, Stylize};
+
+use crate::{
+    config::PenaltiesConfig,
+    constraints::constraint_store::ConstraintStore,
+    report::report,
+    schedule::{DAY_NAMES, Schedule},
+};
+
+/// The background colours of the constraint types, assigned in the order the types first appear
+/// in the constraint store
+pub const TYPE_COLOURS: [Color; 8] = [
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Grey,
+    Color::DarkCyan,
+    Color::DarkYellow,
+];
+
+/// The width of each day column of the grid, including the space separating the columns
+const CELL_WIDTH: usize = 10;
+
+/// The width of the time labels of the grid, including the space after them
+const LABEL_WIDTH: usize = 6;
+
+/// Assigns a colour to each constraint type, in the order the types first appear
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints
+///
+/// # Returns
+/// * `HashMap<&str, Color>` - The colour of each constraint type, by name
+pub fn type_colours(constraints: &ConstraintStore) -> HashMap<&str, Color> {
+    let mut type_colours: HashMap<&str, Color> = HashMap::new();
+    for constraint in constraints {
+        let next_colour = TYPE_COLOURS[type_colours.len() % TYPE_COLOURS.len()];
+        type_colours
+            .entry(constraint.name.as_str())
+            .or_insert(next_colour);
+    }
+    type_colours
+}
+
+/// Renders a schedule as a compact grid of the week, with a column per day and a row per window,
+/// from the earliest to the latest occupied window
+///
+/// Each constraint is named at the window it starts at, and penalised constraints are marked with
+/// a `!`. When coloured, each constraint type has its own background colour and penalised
+/// constraints are written in bold red. Otherwise the later windows of a constraint are marked
+/// with a bar
+///
+/// # Arguments
+/// * `schedule` - The schedule to render
+/// * `constraints` - The constraint store containing the constraints of the schedule
+/// * `penalties_config` - The configuration specifying the values used for penalties
+/// * `colour` - Whether to colour the grid with ANSI escape codes
+///
+/// # Returns
+/// * `Vec<String>` - The lines of the grid, followed by a legend
+pub fn render_grid(
+    schedule: &Schedule,
+    constraints: &ConstraintStore,
+    penalties_config: &PenaltiesConfig,
+    colour: bool,
+) -> Vec<String> {
+    let occupied_windows: Vec<usize> = (0..48)
+        .filter(|window| schedule.grid.iter().any(|day| day[*window].is_some()))
+        .collect();
+    let (Some(first_window), Some(last_window)) =
+        (occupied_windows.first(), occupied_windows.last())
+    else {
+        return vec!["The schedule is empty".to_string()];
+    };
+
+    let mut type_colours = type_colours(constraints);
+    let penalised: HashMap<u32, bool> = constraints
+        .into_iter()
+        .map(|constraint| {
+            let penalty = constraint.calculate_penalty(schedule, constraints, penalties_config);
+            (constraint.id, penalty > 0)
+        })
+        .collect();
+
+    let mut header = " ".repeat(LABEL_WIDTH);
+    for day_name in DAY_NAMES {
+        header += &format!("{:<width$}", day_name, width = CELL_WIDTH);
+    }
+    let mut lines = vec![header.trim_end().to_string()];
+
+    for window in *first_window..=*last_window {
+        let mut line = format!("{:02}:{:02} ", window / 2, (window % 2) * 30);
+        for day in 0..7 {
+            let Some(constraint_id) = schedule.grid[day][window] else {
+                line += &" ".repeat(CELL_WIDTH);
+                continue;
+            };
+            let name = constraints
+                .get_constraint(constraint_id)
+                .map_or("?", |constraint| constraint.name.as_str());
+            let is_penalised = penalised.get(&constraint_id).copied().unwrap_or_default();
+
+            let text = match window > 0 && schedule.grid[day][window - 1] == Some(constraint_id) {
+                true if colour => String::new(),
+                true => "│".to_string(),
+                false if is_penalised => format!("!{}:{}", name, constraint_id),
+                false => format!("{}:{}", name, constraint_id),
+            };
+            let text = fit(&text, CELL_WIDTH - 1);
+            if !colour {
+                line += &format!("{} ", text);
+                continue;
+            }
+
+            let background = type_colours.get(name).copied().unwrap_or(Color::White);
+            let styled = match is_penalised {
+                true => text.with(Color::DarkRed).on(background).bold(),
+                false => text.with(Color::Black).on(background),
+            };
+            line += &format!("{} ", styled);
+        }
+        lines.push(line.trim_end().to_string());
+    }
+
+    lines.push(String::new());
+    if colour {
+        let mut legend: Vec<String> = Vec::new();
+        for constraint in constraints {
+            let name = constraint.name.as_str();
+            if let Some(colour) = type_colours.remove(name) {
+                legend.push(format!("{} {}", "  ".on(colour), name));
+            }
+        }
+        lines.push(legend.join("  "));
+    }
+    lines.push("! penalised".to_string());
+    lines
+}
+
+/// Writes a schedule as a compact grid of the week to the report output
+///
+/// # Arguments
+/// * `schedule` - The schedule to render
+/// * `constraints` - The constraint store containing the constraints of the schedule
+/// * `penalties_config` - The configuration specifying the values used for penalties
+/// * `colour` - Whether to colour the grid with ANSI escape codes
+pub fn print_grid_report(
+    schedule: &Schedule,
+    constraints: &ConstraintStore,
+    penalties_config: &PenaltiesConfig,
+    colour: bool,
+) {
+    report!("=== SCHEDULE GRID ===");
+    for line in render_grid(schedule, constraints, penalties_config, colour) {
+        report!("{}", line);
+    }
+    report!("=====================");
+}
+
+/// Pads or truncates text to a width, marking truncated text with an ellipsis
+pub fn fit(text: &str, width: usize) -> String {
+    match text.chars().count() > width {
+        true => {
+            text.chars()
+                .take(width.saturating_sub(1))
+                .collect::<String>()
+                + "…"
+        }
+        false => format!("{:<width$}", text),
+    }
+}
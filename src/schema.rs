@@ -0,0 +1,76 @@
+use clap::ValueEnum;
+use schemars::{
+    Schema, SchemaGenerator,
+    generate::SchemaSettings,
+    transform::{RecursiveTransform, Transform},
+};
+
+use crate::{
+    config::Config,
+    constraints::{
+        Constraint, constraint_file::ConstraintFile, constraint_store::ConstraintStore,
+        penalty::Penalty,
+    },
+    schedule::Slot,
+};
+
+/// The files and types a JSON Schema can be generated for
+///
+/// * `ConstraintFile` - A json constraint file, where ids and penalties may be left out
+/// * `ConstraintStore` - A constraint store as exported, with every id and penalty listed
+/// * `Constraint` - A single constraint of a constraint store
+/// * `Slot` - A start slot of a constraint
+/// * `Penalty` - A penalty type
+/// * `Config` - The configuration. Every value has a default, so none of them is required
+///
+/// NOTE: Schedules are only exported and imported as csv files, so there is no schema for them
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum SchemaKind {
+    ConstraintFile,
+    ConstraintStore,
+    Constraint,
+    Slot,
+    Penalty,
+    Config,
+}
+
+/// Generates the JSON Schema of a file or type, derived from the Rust types
+///
+/// Files that are read are described as they are deserialized, and files that are written as they
+/// are serialized
+///
+/// # Arguments
+/// * `kind` - The file or type to generate the schema of
+///
+/// # Returns
+/// * `Schema` - The JSON Schema
+pub fn generate_schema(kind: SchemaKind) -> Schema {
+    let deserialize_generator = SchemaSettings::draft2020_12().into_generator();
+    let serialize_generator = SchemaSettings::draft2020_12()
+        .for_serialize()
+        .into_generator();
+
+    match kind {
+        SchemaKind::ConstraintFile => {
+            deserialize_generator.into_root_schema_for::<ConstraintFile>()
+        }
+        SchemaKind::ConstraintStore => {
+            serialize_generator.into_root_schema_for::<ConstraintStore>()
+        }
+        SchemaKind::Constraint => serialize_generator.into_root_schema_for::<Constraint>(),
+        SchemaKind::Slot => deserialize_generator.into_root_schema_for::<Slot>(),
+        SchemaKind::Penalty => deserialize_generator.into_root_schema_for::<Penalty>(),
+        SchemaKind::Config => config_schema(deserialize_generator),
+    }
+}
+
+/// Generates the schema of the configuration, without required values as every value is merged
+/// over its default
+fn config_schema(generator: SchemaGenerator) -> Schema {
+    let mut schema = generator.into_root_schema_for::<Config>();
+    RecursiveTransform(|schema: &mut Schema| {
+        schema.remove("required");
+    })
+    .transform(&mut schema);
+    schema
+}
@@ -0,0 +1,280 @@
+use core::fmt;
+use std::error::Error;
+
+use crate::{
+    config::{Config, PenaltiesConfig, ScoringConfig, ScoringMode},
+    constraints::{ConstraintPriority, constraint_store::ConstraintStore, penalty::Penalty},
+    report::report,
+};
+
+const PENALTIES: [Penalty; 4] = [
+    Penalty::Presence,
+    Penalty::AllowedSlots,
+    Penalty::PreferredSlots,
+    Penalty::Gap,
+];
+
+#[derive(Debug)]
+pub enum ScoringError {
+    ScoreOverflow,
+}
+
+impl fmt::Display for ScoringError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoringError::ScoreOverflow => write!(
+                f,
+                "The lexicographic scores of the constraints or the penalties scaled by their \
+                 tiers do not fit in a u32. Use fewer tiers, lower penalties or the weighted \
+                 scoring mode"
+            ),
+        }
+    }
+}
+
+impl Error for ScoringError {}
+
+/// Compares schedules either on the weighted sum of their penalties or lexicographically by tier
+///
+/// Lexicographic scores are encoded in a single number, by multiplying the penalties of each tier
+/// by one more than the largest score all later tiers can reach together. Comparing the encoded
+/// numbers is then the same as comparing the tiers one at a time, so the searches and the
+/// acceptance criteria work on lexicographic scores unchanged. Within the last tier, penalties keep
+/// their configured values, so the annealing temperatures apply as in the weighted mode
+pub struct Scoring<'a> {
+    scoring_config: &'a ScoringConfig,
+    // tier_multipliers[t] - The multiplier of the penalties of tier t
+    tier_multipliers: Vec<u32>,
+}
+
+impl<'a> Scoring<'a> {
+    /// Prepares the scoring of schedules of the specified constraints
+    ///
+    /// # Arguments
+    /// * `scoring_config` - The scoring mode and the tier of each penalty type
+    /// * `constraints` - The constraint store containing all constraints
+    /// * `penalties_config` - The configuration specifying values used for penalties
+    ///
+    /// # Returns
+    /// * `Scoring` - The scoring
+    /// * `ScoringError` - If the largest lexicographic score does not fit in a u32
+    pub fn new(
+        scoring_config: &'a ScoringConfig,
+        constraints: &ConstraintStore,
+        penalties_config: &PenaltiesConfig,
+    ) -> Result<Self, ScoringError> {
+        if scoring_config.mode == ScoringMode::Weighted {
+            return Ok(Scoring {
+                scoring_config,
+                tier_multipliers: Vec::new(),
+            });
+        }
+
+        let tier_count = PENALTIES
+            .iter()
+            .map(|penalty| scoring_config.tier(*penalty))
+            .max()
+            .unwrap_or(0) as usize
+            + 1;
+        let mut max_tier_scores = vec![0u64; tier_count];
+        for constraint in constraints {
+            for penalty in &constraint.penalties {
+                max_tier_scores[scoring_config.tier(*penalty) as usize] +=
+                    penalty_value(*penalty, &constraint.priority, penalties_config) as u64;
+            }
+        }
+
+        let mut tier_multipliers = vec![0; tier_count];
+        let mut max_score: u64 = 0;
+        for tier in (0..tier_count).rev() {
+            let multiplier = max_score + 1;
+            max_score += max_tier_scores[tier] * multiplier;
+            if max_score > u32::MAX as u64 {
+                return Err(ScoringError::ScoreOverflow);
+            }
+            tier_multipliers[tier] = multiplier as u32;
+        }
+
+        Ok(Scoring {
+            scoring_config,
+            tier_multipliers,
+        })
+    }
+
+    /// Checks whether schedules are compared lexicographically
+    pub fn is_lexicographic(&self) -> bool {
+        self.scoring_config.mode == ScoringMode::Lexicographic
+    }
+
+    /// Returns the penalties the searches should use, so that comparing the total penalties of
+    /// schedules compares their scores
+    ///
+    /// # Arguments
+    /// * `penalties_config` - The configured penalties
+    ///
+    /// # Returns
+    /// * `PenaltiesConfig` - The configured penalties, multiplied by the multiplier of their tier
+    ///   when comparing lexicographically
+    /// * `ScoringError` - If a multiplied penalty does not fit in a u32
+    pub fn search_penalties_config(
+        &self,
+        penalties_config: &PenaltiesConfig,
+    ) -> Result<PenaltiesConfig, ScoringError> {
+        if !self.is_lexicographic() {
+            return Ok(penalties_config.clone());
+        }
+
+        let scale = |value: u32, penalty: Penalty| {
+            value
+                .checked_mul(self.tier_multipliers[self.scoring_config.tier(penalty) as usize])
+                .ok_or(ScoringError::ScoreOverflow)
+        };
+        Ok(PenaltiesConfig {
+            presence_high: scale(penalties_config.presence_high, Penalty::Presence)?,
+            presence_low: scale(penalties_config.presence_low, Penalty::Presence)?,
+            allowed_slots_high: scale(penalties_config.allowed_slots_high, Penalty::AllowedSlots)?,
+            allowed_slots_low: scale(penalties_config.allowed_slots_low, Penalty::AllowedSlots)?,
+            preferred_slots_high: scale(
+                penalties_config.preferred_slots_high,
+                Penalty::PreferredSlots,
+            )?,
+            preferred_slots_low: scale(
+                penalties_config.preferred_slots_low,
+                Penalty::PreferredSlots,
+            )?,
+            gap_high: scale(penalties_config.gap_high, Penalty::Gap)?,
+            gap_low: scale(penalties_config.gap_low, Penalty::Gap)?,
+        })
+    }
+
+    /// Returns the configuration the searches should run with, with the search penalties in place
+    /// of the configured penalties (see `search_penalties_config`)
+    ///
+    /// # Arguments
+    /// * `config` - The configuration of the search
+    ///
+    /// # Returns
+    /// * `Config` - The configuration with the search penalties
+    /// * `ScoringError` - If a multiplied penalty does not fit in a u32
+    pub fn search_config(&self, config: &Config) -> Result<Config, ScoringError> {
+        Ok(Config {
+            penalties_config: self.search_penalties_config(&config.penalties_config)?,
+            ..config.clone()
+        })
+    }
+
+    /// Splits a total penalty calculated with the search penalties into the score of each tier
+    ///
+    /// # Arguments
+    /// * `total_penalty` - The total penalty calculated with the search penalties
+    ///
+    /// # Returns
+    /// * `Vec<u32>` - The score of each tier, or only the total penalty when comparing on the
+    ///   weighted sum
+    pub fn tier_scores(&self, total_penalty: u32) -> Vec<u32> {
+        if !self.is_lexicographic() {
+            return vec![total_penalty];
+        }
+
+        let mut remainder = total_penalty;
+        self.tier_multipliers
+            .iter()
+            .map(|multiplier| {
+                let score = remainder / multiplier;
+                remainder %= multiplier;
+                score
+            })
+            .collect()
+    }
+
+    /// Formats a total penalty calculated with the search penalties, as a tuple of the tier scores
+    /// when comparing lexicographically
+    pub fn format_score(&self, total_penalty: u32) -> String {
+        if !self.is_lexicographic() {
+            return total_penalty.to_string();
+        }
+
+        let tier_scores: Vec<String> = self
+            .tier_scores(total_penalty)
+            .iter()
+            .map(|score| score.to_string())
+            .collect();
+        format!("({})", tier_scores.join(", "))
+    }
+
+    /// Formats the difference between two total penalties calculated with the search penalties,
+    /// tier by tier when comparing lexicographically
+    pub fn format_difference(&self, total_penalty: u32, other_total_penalty: u32) -> String {
+        let differences: Vec<String> = self
+            .tier_scores(total_penalty)
+            .iter()
+            .zip(self.tier_scores(other_total_penalty))
+            .map(|(score, other_score)| format!("{:+}", *score as i64 - other_score as i64))
+            .collect();
+
+        match self.is_lexicographic() {
+            true => format!("({})", differences.join(", ")),
+            false => differences.join(""),
+        }
+    }
+
+    /// Writes the score of the best schedule tier by tier to the report output
+    ///
+    /// Nothing is written when comparing on the weighted sum
+    ///
+    /// # Arguments
+    /// * `total_penalty` - The total penalty of the schedule calculated with the search penalties
+    pub fn print_score_report(&self, total_penalty: u32) {
+        if !self.is_lexicographic() {
+            return;
+        }
+
+        report!("=== LEXICOGRAPHIC SCORE ===");
+        report!("SCORE: {}", self.format_score(total_penalty));
+        for (tier, score) in self.tier_scores(total_penalty).iter().enumerate() {
+            let penalties: Vec<String> = PENALTIES
+                .iter()
+                .filter(|penalty| self.scoring_config.tier(**penalty) as usize == tier)
+                .map(|penalty| penalty.to_string())
+                .collect();
+            report!(
+                "  └─ Tier {} ({}): {}",
+                tier,
+                match penalties.is_empty() {
+                    true => "no penalty types".to_string(),
+                    false => penalties.join(", "),
+                },
+                score
+            );
+        }
+        report!("===========================");
+    }
+}
+
+/// Returns the configured value of a penalty type for a priority
+fn penalty_value(
+    penalty: Penalty,
+    priority: &ConstraintPriority,
+    penalties_config: &PenaltiesConfig,
+) -> u32 {
+    let (high, low) = match penalty {
+        Penalty::Presence => (
+            penalties_config.presence_high,
+            penalties_config.presence_low,
+        ),
+        Penalty::AllowedSlots => (
+            penalties_config.allowed_slots_high,
+            penalties_config.allowed_slots_low,
+        ),
+        Penalty::PreferredSlots => (
+            penalties_config.preferred_slots_high,
+            penalties_config.preferred_slots_low,
+        ),
+        Penalty::Gap => (penalties_config.gap_high, penalties_config.gap_low),
+    };
+
+    match priority {
+        ConstraintPriority::High => high,
+        ConstraintPriority::Low => low,
+    }
+}
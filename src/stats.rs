@@ -1,4 +1,52 @@
-use log::info;
+use crate::{
+    alternatives::AlternativeArchive, multi_objective::pareto_archive::ParetoSolution,
+    report::report, stop_condition::StopReason,
+};
+
+/// Statistics about the exchanges attempted between two parallel tempering chains at neighbouring
+/// temperatures
+#[derive(Default, Clone)]
+pub struct ReplicaExchangeStats {
+    pub lower_temperature: f32,
+    pub upper_temperature: f32,
+    pub attempt_count: u32,
+    pub accepted_count: u32,
+}
+
+/// Statistics about a single island of the island search mode
+#[derive(Default, Clone)]
+pub struct IslandStats {
+    pub island: u32,
+    pub best_total_penalty: u32,
+    pub migrations_sent: u32,
+    pub migrations_received: u32,
+}
+
+/// Statistics about an operator chosen adaptively
+#[derive(Default, Clone)]
+pub struct OperatorStats {
+    pub name: String,
+    pub weight: f32,
+    pub use_count: u32,
+    pub improvement_count: u32,
+}
+
+/// The weights of the optimisation strategies (move, unschedule, swap) at an iteration of an
+/// adaptive run
+#[derive(Default, Clone)]
+pub struct StrategyWeightSnapshot {
+    pub iteration: u32,
+    pub weights: Vec<f32>,
+}
+
+/// Statistics about a run of the exact search mode
+#[derive(Default, Clone)]
+pub struct ExactSearchStats {
+    pub node_count: u64,
+    pub best_total_penalty: u32,
+    pub lower_bound: u32,
+    pub proven_optimal: bool,
+}
 
 /// A simple struct to store simple statistics about a run of the optimisation algorithm
 #[derive(Default)]
@@ -12,10 +60,54 @@ pub struct OptimisationStats {
 
     pub revert_count: u32,
     pub reset_count: u32,
+    pub reheat_count: u32,
+
+    // Only populated by the genetic search mode
+    pub generation_count: u32,
+    pub crossover_count: u32,
+
+    // Set when the run was stopped before completing all of its iterations
+    pub stop_reason: Option<StopReason>,
+
+    // Only populated by the parallel tempering search mode
+    pub replica_exchanges: Vec<ReplicaExchangeStats>,
+    // Only populated by the island search mode
+    pub islands: Vec<IslandStats>,
+    // Only populated by the large neighbourhood search mode
+    pub ruin_operators: Vec<OperatorStats>,
+    // Only populated when the optimisation strategies are chosen adaptively
+    pub strategy_operators: Vec<OperatorStats>,
+    pub strategy_weight_history: Vec<StrategyWeightSnapshot>,
+    // Only populated by the exact search mode
+    pub exact_search: Option<ExactSearchStats>,
+    // Only populated by the multi-objective search mode, ordered by total penalty
+    pub pareto_front: Vec<ParetoSolution>,
+
+    // The diverse good schedules found, only populated when alternatives are requested
+    pub alternative_archive: AlternativeArchive,
 }
 
 impl OptimisationStats {
-    /// Logs a report of the optimisation algorithms statistics to standard out
+    /// Adds the counts of another run's statistics to these statistics, and offers its
+    /// alternative schedules to the archive of these statistics
+    ///
+    /// # Arguments
+    /// * `other` - The statistics to add
+    pub fn merge(&mut self, other: &OptimisationStats) {
+        self.move_count += other.move_count;
+        self.schedule_count += other.schedule_count;
+        self.unscheduling_scheduled_count += other.unscheduling_scheduled_count;
+        self.unscheduling_unscheduled_count += other.unscheduling_unscheduled_count;
+        self.swap_count += other.swap_count;
+        self.revert_count += other.revert_count;
+        self.reset_count += other.reset_count;
+        self.reheat_count += other.reheat_count;
+        self.generation_count += other.generation_count;
+        self.crossover_count += other.crossover_count;
+        self.alternative_archive.merge(&other.alternative_archive);
+    }
+
+    /// Writes a report of the optimisation algorithms statistics to the report output
     ///
     /// NOTE: This code snippet is AI generated
     pub fn generate_optimisation_report(&self) {
@@ -24,25 +116,125 @@ impl OptimisationStats {
             + self.unscheduling_scheduled_count
             + self.unscheduling_unscheduled_count;
 
-        info!("─── OPTIMISATION STATS ───");
+        report!("─── OPTIMISATION STATS ───");
 
-        info!("▶ Throughput");
-        info!("  ├─ Total Iterations: {}", total_iterations);
-        info!("  ├─ Total resets: {}", self.reset_count);
-        info!("  ├─ Total reverts: {}", self.revert_count);
+        report!("▶ Throughput");
+        report!("  ├─ Total Iterations: {}", total_iterations);
+        report!("  ├─ Total resets: {}", self.reset_count);
+        report!("  ├─ Total reheats: {}", self.reheat_count);
+        if self.generation_count > 0 {
+            report!("  ├─ Total generations: {}", self.generation_count);
+            report!("  ├─ Total crossovers: {}", self.crossover_count);
+        }
+        report!("  ├─ Total reverts: {}", self.revert_count);
+        if let Some(stop_reason) = self.stop_reason {
+            report!("  ├─ Stopped early: {}", stop_reason);
+        }
 
-        info!("▶ Mutation Types");
-        info!("  ├─ Moves: {}", self.move_count);
-        info!("  ├─ Swaps: {}", self.swap_count);
-        info!("  ├─ New schedulings: {}", self.schedule_count);
-        info!(
+        report!("▶ Mutation Types");
+        report!("  ├─ Moves: {}", self.move_count);
+        report!("  ├─ Swaps: {}", self.swap_count);
+        report!("  ├─ New schedulings: {}", self.schedule_count);
+        report!(
             "  └─ Mass unscheduling scheduled: {}",
             self.unscheduling_scheduled_count
         );
-        info!(
+        report!(
             "  └─ Mass unscheduling unscheduled: {}",
             self.unscheduling_unscheduled_count
         );
-        info!("──────────────────────────");
+        if !self.replica_exchanges.is_empty() {
+            report!("▶ Replica Exchanges");
+            for exchange in &self.replica_exchanges {
+                let acceptance_rate = match exchange.attempt_count {
+                    0 => 0.0,
+                    attempts => exchange.accepted_count as f32 / attempts as f32 * 100.0,
+                };
+                report!(
+                    "  └─ T {:.2} <-> T {:.2}: {}/{} accepted ({:.1}%)",
+                    exchange.lower_temperature,
+                    exchange.upper_temperature,
+                    exchange.accepted_count,
+                    exchange.attempt_count,
+                    acceptance_rate
+                );
+            }
+        }
+        if !self.islands.is_empty() {
+            report!("▶ Islands");
+            for island in &self.islands {
+                report!(
+                    "  └─ Island {}: best penalty {} | migrations sent {} | migrations received {}",
+                    island.island,
+                    island.best_total_penalty,
+                    island.migrations_sent,
+                    island.migrations_received
+                );
+            }
+        }
+        if !self.ruin_operators.is_empty() {
+            report!("▶ Ruin Operators");
+            for operator in &self.ruin_operators {
+                report!(
+                    "  └─ {}: final weight {:.2} | used {} | improved {}",
+                    operator.name, operator.weight, operator.use_count, operator.improvement_count
+                );
+            }
+        }
+        if !self.strategy_operators.is_empty() {
+            report!("▶ Optimisation Strategies");
+            for operator in &self.strategy_operators {
+                report!(
+                    "  └─ {}: final weight {:.3} | used {} | improved {}",
+                    operator.name, operator.weight, operator.use_count, operator.improvement_count
+                );
+            }
+        }
+        if !self.strategy_weight_history.is_empty() {
+            report!("▶ Strategy Weight History (move / unschedule / swap)");
+            for snapshot in &self.strategy_weight_history {
+                let weights: Vec<String> = snapshot
+                    .weights
+                    .iter()
+                    .map(|weight| format!("{:.3}", weight))
+                    .collect();
+                report!(
+                    "  └─ Iteration {}: {}",
+                    snapshot.iteration,
+                    weights.join(" / ")
+                );
+            }
+        }
+        if let Some(exact_search) = &self.exact_search {
+            let gap = match exact_search.best_total_penalty {
+                0 => 0.0,
+                best => (best - exact_search.lower_bound) as f32 / best as f32 * 100.0,
+            };
+            report!("▶ Exact Search");
+            report!("  ├─ Nodes explored: {}", exact_search.node_count);
+            report!("  ├─ Lower bound: {}", exact_search.lower_bound);
+            if exact_search.proven_optimal {
+                report!("  └─ Proven optimal: yes");
+            } else {
+                report!("  └─ Proven optimal: no (gap {:.1}%)", gap);
+            }
+        }
+        if !self.pareto_front.is_empty() {
+            report!("▶ Pareto Front");
+            for (i, solution) in self.pareto_front.iter().enumerate() {
+                let scores: Vec<String> = solution
+                    .scores
+                    .iter()
+                    .map(|(penalty, score)| format!("{}: {}", penalty, score))
+                    .collect();
+                report!(
+                    "  └─ Schedule {}: {} | total {}",
+                    i + 1,
+                    scores.join(" | "),
+                    solution.total_penalty
+                );
+            }
+        }
+        report!("──────────────────────────");
     }
 }
@@ -0,0 +1,97 @@
+use core::fmt;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use log::warn;
+
+/// Set once the user has requested the optimisation to stop (Ctrl-C)
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// The reason an optimisation run was stopped before completing all of its iterations
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StopReason {
+    TimeLimit,
+    Interrupted,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StopReason::TimeLimit => write!(f, "Time limit reached"),
+            StopReason::Interrupted => write!(f, "Interrupted"),
+        }
+    }
+}
+
+/// Registers a SIGINT (Ctrl-C) handler that requests all running optimisations to stop
+///
+/// The first interrupt lets the runs finish their current iteration so that the best schedule
+/// found so far can still be exported. A second interrupt terminates the process immediately
+pub fn register_interrupt_handler() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
+        }
+        warn!("Interrupt received. Stopping optimisation and keeping the best schedule so far");
+    })
+}
+
+/// Returns whether the user has requested the optimisation to stop
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Wall-clock based stopping rule shared by the global search and each of its runs
+#[derive(Clone, Copy, Debug)]
+pub struct StopCondition {
+    deadline: Option<Instant>,
+}
+
+impl StopCondition {
+    /// Creates a stop condition that expires after the specified time limit
+    ///
+    /// # Arguments
+    /// * `time_limit` - The wall-clock time allowed from now. `None`, or a limit past the latest
+    ///   representable instant, for no limit
+    pub fn new(time_limit: Option<Duration>) -> Self {
+        StopCondition {
+            deadline: time_limit.and_then(|limit| Instant::now().checked_add(limit)),
+        }
+    }
+
+    /// Derives the stop condition for a single run starting now
+    ///
+    /// The run stops at whichever comes first out of its own time limit and the deadline of
+    /// this stop condition
+    ///
+    /// # Arguments
+    /// * `run_time_limit` - The wall-clock time allowed for the run. `None`, or a limit past the
+    ///   latest representable instant, for no limit
+    pub fn for_run(&self, run_time_limit: Option<Duration>) -> Self {
+        let run_deadline = run_time_limit.and_then(|limit| Instant::now().checked_add(limit));
+        let deadline = match (self.deadline, run_deadline) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+
+        StopCondition { deadline }
+    }
+
+    /// Checks whether the optimisation should stop
+    ///
+    /// # Returns
+    /// * `StopReason` - The reason to stop
+    /// * `None` - If the optimisation can carry on
+    pub fn check(&self) -> Option<StopReason> {
+        if is_interrupted() {
+            return Some(StopReason::Interrupted);
+        }
+
+        match self.deadline {
+            Some(deadline) if Instant::now() >= deadline => Some(StopReason::TimeLimit),
+            _ => None,
+        }
+    }
+}
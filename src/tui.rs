@@ -0,0 +1,250 @@
+pub mod editor;
+
+use std::{collections::HashMap, error::Error};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use log::LevelFilter;
+use ratatui::{
+    DefaultTerminal, Frame,
+    layout::{Constraint as Size, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+};
+
+use crate::{
+    schedule::{
+        DAY_NAMES,
+        grid_renderer::{fit, type_colours},
+    },
+    tui::editor::{Direction, Editor},
+};
+
+/// The width of the panel describing the selected constraint
+const PANEL_WIDTH: u16 = 36;
+
+/// The width of the time labels of the grid
+const LABEL_WIDTH: usize = 6;
+
+/// The keys of the editor, shown in the panel
+const KEY_HELP: [&str; 6] = [
+    "Tab/Shift+Tab  select",
+    "Arrows         move (and pin)",
+    "Space          pin / unpin",
+    "r              re-optimise",
+    "s              save",
+    "q              quit",
+];
+
+/// Runs the interactive schedule editor in the terminal until it is quit
+///
+/// Logging is silenced while the editor is open, as log lines would be drawn over the editor
+///
+/// # Arguments
+/// * `editor` - The editor of the schedule
+pub fn run_editor(editor: &mut Editor) -> Result<(), Box<dyn Error>> {
+    let max_level = log::max_level();
+    log::set_max_level(LevelFilter::Off);
+    let mut terminal = ratatui::init();
+    let result = edit(&mut terminal, editor);
+    ratatui::restore();
+    log::set_max_level(max_level);
+    result
+}
+
+/// Draws the editor and handles key presses until the editor is quit
+fn edit(terminal: &mut DefaultTerminal, editor: &mut Editor) -> Result<(), Box<dyn Error>> {
+    let mut first_window = 0;
+    loop {
+        terminal.draw(|frame| draw(frame, editor, &mut first_window))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            KeyCode::Tab => editor.select_next(),
+            KeyCode::BackTab => editor.select_previous(),
+            KeyCode::Up => editor.move_selected(Direction::Up),
+            KeyCode::Down => editor.move_selected(Direction::Down),
+            KeyCode::Left => editor.move_selected(Direction::Left),
+            KeyCode::Right => editor.move_selected(Direction::Right),
+            KeyCode::Char(' ') => editor.toggle_pin(),
+            KeyCode::Char('r') => editor.reoptimise(),
+            KeyCode::Char('s') => {
+                if let Err(error) = editor.save() {
+                    editor.message = format!("Could not save: {}", error);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draws the week grid, the panel of the selected constraint and the status line
+///
+/// # Arguments
+/// * `frame` - The frame to draw in
+/// * `editor` - The editor of the schedule
+/// * `first_window` - The first window shown in the grid, scrolled to keep the selected
+///   constraint in view
+fn draw(frame: &mut Frame, editor: &Editor, first_window: &mut usize) {
+    let [main_area, status_area] =
+        Layout::vertical([Size::Min(0), Size::Length(1)]).areas(frame.area());
+    let [grid_area, panel_area] =
+        Layout::horizontal([Size::Min(0), Size::Length(PANEL_WIDTH)]).areas(main_area);
+
+    draw_grid(frame, editor, grid_area, first_window);
+    frame.render_widget(
+        Paragraph::new(panel_lines(editor)).block(Block::bordered().title(" Selected ")),
+        panel_area,
+    );
+    frame.render_widget(Paragraph::new(editor.message.as_str()), status_area);
+}
+
+/// Draws the visible windows of the week, with a column per day and a row per window
+///
+/// Each constraint is coloured by its type and named at the window it starts at. Penalised
+/// constraints are drawn in bold red, pinned constraints are marked with a `*` and the selected
+/// constraint is reversed
+fn draw_grid(frame: &mut Frame, editor: &Editor, area: Rect, first_window: &mut usize) {
+    let block = Block::bordered().title(" Week ");
+    let inner_area = block.inner(area);
+    frame.render_widget(block, area);
+
+    let visible_windows = (inner_area.height as usize).saturating_sub(1).clamp(1, 48);
+    let cell_width = (inner_area.width as usize)
+        .saturating_sub(LABEL_WIDTH)
+        .checked_div(7)
+        .unwrap_or_default()
+        .max(2);
+
+    let schedule = editor.schedule();
+    let selected = editor.selected_constraint();
+    if let Some(slot) = schedule.get_scheduled_slot_for_constraint(selected.id) {
+        let (start, end) = (
+            slot.window as usize,
+            (slot.window + selected.duration) as usize,
+        );
+        if start < *first_window {
+            *first_window = start;
+        } else if end > *first_window + visible_windows {
+            *first_window = end.saturating_sub(visible_windows).min(start);
+        }
+    }
+    *first_window = (*first_window).min(48 - visible_windows);
+
+    let type_colours = type_colours(editor.constraints());
+    let penalised: HashMap<u32, bool> = editor
+        .constraints()
+        .into_iter()
+        .map(|constraint| (constraint.id, editor.is_penalised(constraint)))
+        .collect();
+
+    let mut header = vec![Span::raw(" ".repeat(LABEL_WIDTH))];
+    header.extend(
+        DAY_NAMES
+            .iter()
+            .map(|day_name| Span::raw(fit(day_name, cell_width))),
+    );
+    let mut lines = vec![Line::from(header)];
+
+    for window in *first_window..*first_window + visible_windows {
+        let mut spans = vec![Span::raw(format!(
+            "{:02}:{:02} ",
+            window / 2,
+            (window % 2) * 30
+        ))];
+        for day in 0..7 {
+            let Some(constraint_id) = schedule.grid[day][window] else {
+                spans.push(Span::raw(" ".repeat(cell_width)));
+                continue;
+            };
+            let constraint = editor.constraints().get_constraint(constraint_id);
+            let name = constraint.map_or("?", |constraint| constraint.name.as_str());
+
+            let text = match window > 0 && schedule.grid[day][window - 1] == Some(constraint_id) {
+                true => String::new(),
+                false if editor.is_pinned(constraint_id) => {
+                    format!("*{}:{}", name, constraint_id)
+                }
+                false => format!("{}:{}", name, constraint_id),
+            };
+            let mut style = Style::default()
+                .bg(type_colours
+                    .get(name)
+                    .map_or(Color::White, |colour| Color::from(*colour)))
+                .fg(Color::Black);
+            if penalised.get(&constraint_id).copied().unwrap_or_default() {
+                style = style.fg(Color::Red).add_modifier(Modifier::BOLD);
+            }
+            if constraint_id == selected.id {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            spans.push(Span::styled(fit(&text, cell_width - 1), style));
+            spans.push(Span::raw(" "));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    frame.render_widget(Paragraph::new(lines), inner_area);
+}
+
+/// Describes the selected constraint, its penalties, the total penalty and the keys
+fn panel_lines(editor: &Editor) -> Vec<Line<'static>> {
+    let constraint = editor.selected_constraint();
+    let slot = editor
+        .schedule()
+        .get_scheduled_slot_for_constraint(constraint.id)
+        .map_or("unscheduled".to_string(), |slot| slot.to_string());
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+
+    let mut lines = vec![
+        Line::styled(format!("{}:{}", constraint.name, constraint.id), bold),
+        Line::raw(format!("Slot: {}", slot)),
+        Line::raw(format!("Priority: {:?}", constraint.priority)),
+        Line::raw(format!("Duration: {} windows", constraint.duration)),
+        Line::raw(format!(
+            "Pinned: {}",
+            if editor.is_pinned(constraint.id) {
+                "yes"
+            } else {
+                "no"
+            }
+        )),
+    ];
+    if !constraint.tags.is_empty() {
+        lines.push(Line::raw(format!("Tags: {}", constraint.tags.join(", "))));
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::styled("Penalties", bold));
+    for (penalty, value) in editor.detailed_penalty(constraint) {
+        let style = match value > 0 {
+            true => Style::default().fg(Color::Red),
+            false => Style::default(),
+        };
+        lines.push(Line::styled(format!("  {}: {}", penalty, value), style));
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        format!("Total penalty: {}", editor.total_penalty()),
+        bold,
+    ));
+    lines.push(Line::raw(format!(
+        "Since opened: {:+}",
+        editor.penalty_change()
+    )));
+    if let Some(last_change) = editor.last_change() {
+        lines.push(Line::raw(format!("Last edit: {:+}", last_change)));
+    }
+
+    lines.push(Line::raw(""));
+    lines.extend(KEY_HELP.iter().map(|help| Line::raw(*help)));
+    lines
+}
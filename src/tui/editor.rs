@@ -0,0 +1,299 @@
+use std::{collections::HashSet, error::Error};
+
+use crate::{
+    config::Config,
+    constraints::{
+        Constraint, constraint_store::ConstraintStore, penalties::calculate_penalties,
+        penalty::Penalty,
+    },
+    explanation::{explain_schedule, export_explanations},
+    large_neighbourhood_search::recreate::recreate,
+    schedule::{Schedule, Slot},
+    scoring::{Scoring, ScoringError},
+};
+
+/// The number of times the unpinned constraints are recreated when re-optimising, keeping the
+/// best schedule
+const REOPTIMISE_ATTEMPTS: u32 = 20;
+
+/// A direction to move the selected constraint in
+///
+/// * `Up` - One window earlier on the same day
+/// * `Down` - One window later on the same day
+/// * `Left` - The same window on the previous day
+/// * `Right` - The same window on the next day
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The state of the interactive schedule editor
+///
+/// Constraints moved by hand are pinned, so re-optimising schedules the unpinned constraints
+/// around the edits
+///
+/// * `constraints` - The constraint store containing the constraints of the schedule
+/// * `config` - The configuration specifying the values used for penalties and the exports
+/// * `search_config` - The configuration the unpinned constraints are re-optimised with, with
+///   the penalties scaled by the scoring
+/// * `schedule` - The edited schedule
+/// * `pinned` - The ids of the pinned constraints
+/// * `selected` - The index (in the constraint store) of the selected constraint
+/// * `loaded_penalty` - The total penalty of the schedule the editor was opened with
+/// * `last_change` - The change in the total penalty caused by the last edit
+/// * `message` - The result of the last action, shown in the status line
+pub struct Editor<'a> {
+    constraints: &'a ConstraintStore,
+    config: &'a Config,
+    search_config: Config,
+    schedule: Schedule,
+    pinned: HashSet<u32>,
+    selected: usize,
+    loaded_penalty: u32,
+    last_change: Option<i64>,
+    pub message: String,
+}
+
+impl<'a> Editor<'a> {
+    /// Opens the editor on a schedule, with the first constraint selected and nothing pinned
+    ///
+    /// # Arguments
+    /// * `constraints` - The constraint store containing the constraints of the schedule
+    /// * `config` - The configuration specifying the values used for penalties and the exports
+    /// * `schedule` - The schedule to edit
+    ///
+    /// # Returns
+    /// * `Editor` - The editor
+    /// * `ScoringError` - If the scoring of the configuration cannot be applied to the constraints
+    pub fn new(
+        constraints: &'a ConstraintStore,
+        config: &'a Config,
+        schedule: Schedule,
+    ) -> Result<Self, ScoringError> {
+        let scoring = Scoring::new(
+            &config.scoring_config,
+            constraints,
+            &config.penalties_config,
+        )?;
+        let search_config = scoring.search_config(config)?;
+        let (_, loaded_penalty) =
+            calculate_penalties(constraints, &schedule, &config.penalties_config);
+
+        Ok(Editor {
+            constraints,
+            config,
+            search_config,
+            schedule,
+            pinned: HashSet::new(),
+            selected: 0,
+            loaded_penalty,
+            last_change: None,
+            message: String::new(),
+        })
+    }
+
+    /// Returns the constraint store containing the constraints of the schedule
+    pub fn constraints(&self) -> &ConstraintStore {
+        self.constraints
+    }
+
+    /// Returns the edited schedule
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+
+    /// Returns the selected constraint
+    pub fn selected_constraint(&self) -> &Constraint {
+        self.constraints
+            .into_iter()
+            .nth(self.selected)
+            .expect("LOGIC ERROR: The editor is only opened on constraints")
+    }
+
+    /// Selects the next constraint of the constraint store, wrapping around
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.constraints.len();
+    }
+
+    /// Selects the previous constraint of the constraint store, wrapping around
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + self.constraints.len() - 1) % self.constraints.len();
+    }
+
+    /// Checks whether a constraint is pinned
+    pub fn is_pinned(&self, constraint_id: u32) -> bool {
+        self.pinned.contains(&constraint_id)
+    }
+
+    /// Pins the selected constraint, or unpins it if it is pinned
+    pub fn toggle_pin(&mut self) {
+        let constraint = self.selected_constraint();
+        let (constraint_id, name) = (constraint.id, constraint.name.clone());
+        self.message = match self.pinned.remove(&constraint_id) {
+            true => format!("Unpinned {}:{}", name, constraint_id),
+            false => {
+                self.pinned.insert(constraint_id);
+                format!("Pinned {}:{}", name, constraint_id)
+            }
+        };
+    }
+
+    /// Moves the selected constraint by one window or day, and pins it
+    ///
+    /// The constraint is left in place if it is not scheduled, or the slot in the direction is
+    /// out of the week or overlaps another constraint
+    ///
+    /// # Arguments
+    /// * `direction` - The direction to move the constraint in
+    pub fn move_selected(&mut self, direction: Direction) {
+        let constraint = self.selected_constraint();
+        let (constraint_id, duration) = (constraint.id, constraint.duration);
+        let Some(slot) = self
+            .schedule
+            .get_scheduled_slot_for_constraint(constraint_id)
+            .cloned()
+        else {
+            self.message = "The selected constraint is not scheduled".to_string();
+            return;
+        };
+
+        let new_slot = match direction {
+            Direction::Up => slot
+                .window
+                .checked_sub(1)
+                .map(|window| Slot { window, ..slot }),
+            Direction::Down => Some(Slot {
+                window: slot.window + 1,
+                ..slot
+            }),
+            Direction::Left => slot.day.checked_sub(1).map(|day| Slot { day, ..slot }),
+            Direction::Right => Some(Slot {
+                day: slot.day + 1,
+                ..slot
+            }),
+        };
+        let Some(new_slot) = new_slot.filter(|new_slot| {
+            self.schedule
+                .is_duration_free_or_owned_by(&constraint_id, duration, new_slot)
+        }) else {
+            self.message = "The constraint cannot move there".to_string();
+            return;
+        };
+
+        let penalty_before = self.total_penalty();
+        self.schedule
+            .move_constraint(constraint_id, duration, new_slot.clone())
+            .expect("LOGIC ERROR: Could not move scheduled constraint");
+        self.pinned.insert(constraint_id);
+        self.last_change = Some(self.total_penalty() as i64 - penalty_before as i64);
+        self.message = format!("Moved to {}", new_slot);
+    }
+
+    /// Re-optimises the unpinned constraints around the pinned constraints
+    ///
+    /// The unpinned constraints are unscheduled and recreated with the configured recreate
+    /// method several times, and the best schedule is kept if it improves on the current
+    /// schedule
+    pub fn reoptimise(&mut self) {
+        let search_penalty = |schedule: &Schedule| {
+            calculate_penalties(
+                self.constraints,
+                schedule,
+                &self.search_config.penalties_config,
+            )
+            .1
+        };
+
+        let mut ruined = self.schedule.clone();
+        for constraint in self.constraints {
+            if !self.pinned.contains(&constraint.id) {
+                let _ = ruined.unschedule_constraint(constraint.id);
+            }
+        }
+
+        let mut best: Option<(Schedule, u32)> = None;
+        for _ in 0..REOPTIMISE_ATTEMPTS {
+            let mut schedule = ruined.clone();
+            recreate(&mut schedule, self.constraints, &self.search_config);
+            let penalty = search_penalty(&schedule);
+            if best
+                .as_ref()
+                .is_none_or(|(_, best_penalty)| penalty < *best_penalty)
+            {
+                best = Some((schedule, penalty));
+            }
+        }
+
+        let penalty_before = self.total_penalty();
+        match best {
+            Some((schedule, penalty)) if penalty < search_penalty(&self.schedule) => {
+                self.schedule = schedule;
+                let change = self.total_penalty() as i64 - penalty_before as i64;
+                self.last_change = Some(change);
+                self.message = format!("Re-optimised the unpinned constraints ({:+})", change);
+            }
+            _ => {
+                self.message = "Re-optimising found no better schedule".to_string();
+            }
+        }
+    }
+
+    /// Exports the schedule to the output path, and the explanations of its penalties when an
+    /// explanation output path is configured
+    pub fn save(&mut self) -> Result<(), Box<dyn Error>> {
+        self.schedule
+            .export_to_csv(self.config.output_path.clone(), self.constraints)?;
+        if let Some(output_path) = &self.config.explanation_config.output_path {
+            let explanations = explain_schedule(
+                self.constraints,
+                &self.schedule,
+                &self.config.penalties_config,
+            );
+            export_explanations(&explanations, output_path)?;
+        }
+
+        self.message = format!("Saved to {}", self.config.output_path);
+        Ok(())
+    }
+
+    /// Returns the total penalty of the schedule, with the configured penalties
+    pub fn total_penalty(&self) -> u32 {
+        let (_, total_penalty) = calculate_penalties(
+            self.constraints,
+            &self.schedule,
+            &self.config.penalties_config,
+        );
+        total_penalty
+    }
+
+    /// Returns the change in the total penalty since the editor was opened
+    pub fn penalty_change(&self) -> i64 {
+        self.total_penalty() as i64 - self.loaded_penalty as i64
+    }
+
+    /// Returns the change in the total penalty caused by the last edit, if anything was edited
+    pub fn last_change(&self) -> Option<i64> {
+        self.last_change
+    }
+
+    /// Returns the penalties a constraint incurs in the schedule, by penalty type
+    pub fn detailed_penalty(&self, constraint: &Constraint) -> Vec<(Penalty, u32)> {
+        constraint.calculate_detailed_penalty(
+            &self.schedule,
+            self.constraints,
+            &self.config.penalties_config,
+        )
+    }
+
+    /// Checks whether a constraint incurs any penalty in the schedule
+    pub fn is_penalised(&self, constraint: &Constraint) -> bool {
+        constraint.calculate_penalty(
+            &self.schedule,
+            self.constraints,
+            &self.config.penalties_config,
+        ) > 0
+    }
+}
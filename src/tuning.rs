@@ -0,0 +1,438 @@
+use std::{
+    error::Error,
+    time::{Duration, Instant},
+};
+
+use log::{info, warn};
+use sobol_burley::sample;
+
+use crate::{
+    config::{Config, TuningConfig},
+    constraints::constraint_store::ConstraintStore,
+    global_search::run_global_search,
+    report::report,
+    scoring::{Scoring, ScoringError},
+    stop_condition::{StopReason, is_interrupted},
+};
+
+/// A configuration of the tuned parameters and the penalties of its solves
+///
+/// * `initial_temperature` - The initial temperature of the local search algorithm
+/// * `cooling_factor` - The cooling factor of the local search algorithm
+/// * `iterations` - The number of iterations of the local search algorithm
+/// * `move_chance` - The chance of the move optimisation strategy
+/// * `unschedule_chance` - The chance of the unschedule optimisation strategy
+/// * `swap_chance` - The chance of the swap optimisation strategy
+/// * `penalties` - The total penalty of each solve, in the order of the rounds
+/// * `eliminated_in_round` - The round the configuration was eliminated in, if eliminated
+#[derive(Clone, Debug)]
+pub struct TuningCandidate {
+    pub initial_temperature: f32,
+    pub cooling_factor: f32,
+    pub iterations: u32,
+    pub move_chance: u32,
+    pub unschedule_chance: u32,
+    pub swap_chance: u32,
+    pub penalties: Vec<u32>,
+    pub eliminated_in_round: Option<u32>,
+}
+
+impl TuningCandidate {
+    /// Creates a candidate with the parameters of a configuration
+    fn from_config(config: &Config) -> Self {
+        TuningCandidate {
+            initial_temperature: config.initial_temperature,
+            cooling_factor: config.cooling_factor,
+            iterations: config.iterations,
+            move_chance: config.optimisation_strategy_config.move_chance,
+            unschedule_chance: config.optimisation_strategy_config.unschedule_chance,
+            swap_chance: config.optimisation_strategy_config.swap_chance,
+            penalties: Vec::new(),
+            eliminated_in_round: None,
+        }
+    }
+
+    /// Draws the parameters of a candidate from the tuning ranges with a Sobol sequence
+    ///
+    /// Temperatures, iterations and the distance of the cooling factor from 1 are drawn on a log
+    /// scale, as their effect depends on their order of magnitude
+    ///
+    /// # Arguments
+    /// * `tuning_config` - The ranges to draw the parameters from
+    /// * `index` - The point of the Sobol sequence to use
+    /// * `random_seed` - The scramble seed of the Sobol sequence
+    fn draw(tuning_config: &TuningConfig, index: u32, random_seed: u32) -> Self {
+        let point = |dimension: u32| sample(index, dimension, random_seed);
+        let log_scale = |min: f32, max: f32, value: f32| min * (max / min).powf(value);
+        let chance = |value: f32| {
+            ((value * (tuning_config.max_strategy_chance + 1) as f32) as u32)
+                .min(tuning_config.max_strategy_chance)
+        };
+
+        let (min_temperature, max_temperature) = tuning_config.temperature_range;
+        let (min_cooling_factor, max_cooling_factor) = tuning_config.cooling_factor_range;
+        let (min_iterations, max_iterations) = tuning_config.iterations_range;
+        let mut candidate = TuningCandidate {
+            initial_temperature: log_scale(min_temperature, max_temperature, point(0)),
+            cooling_factor: 1.0
+                - log_scale(1.0 - min_cooling_factor, 1.0 - max_cooling_factor, point(1)),
+            iterations: log_scale(min_iterations as f32, max_iterations as f32, point(2)).round()
+                as u32,
+            move_chance: chance(point(3)),
+            unschedule_chance: chance(point(4)),
+            swap_chance: chance(point(5)),
+            penalties: Vec::new(),
+            eliminated_in_round: None,
+        };
+
+        if candidate.move_chance + candidate.unschedule_chance + candidate.swap_chance == 0 {
+            candidate.move_chance = 1;
+        }
+        candidate
+    }
+
+    /// Returns a configuration with the parameters of this candidate
+    ///
+    /// # Arguments
+    /// * `config` - The configuration to take the other values from
+    pub fn apply(&self, config: &Config) -> Config {
+        let mut config = config.clone();
+        config.initial_temperature = self.initial_temperature;
+        config.cooling_factor = self.cooling_factor;
+        config.iterations = self.iterations;
+        config.optimisation_strategy_config.move_chance = self.move_chance;
+        config.optimisation_strategy_config.unschedule_chance = self.unschedule_chance;
+        config.optimisation_strategy_config.swap_chance = self.swap_chance;
+        config
+    }
+
+    /// Returns the mean penalty of the solves of this candidate
+    pub fn mean(&self) -> f64 {
+        if self.penalties.is_empty() {
+            return f64::INFINITY;
+        }
+
+        self.penalties
+            .iter()
+            .map(|penalty| *penalty as f64)
+            .sum::<f64>()
+            / self.penalties.len() as f64
+    }
+
+    /// Returns the sample variance of the penalties of the solves of this candidate
+    pub fn variance(&self) -> f64 {
+        if self.penalties.len() < 2 {
+            return 0.0;
+        }
+
+        let mean = self.mean();
+        self.penalties
+            .iter()
+            .map(|penalty| (*penalty as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (self.penalties.len() - 1) as f64
+    }
+
+    /// Describes the tuned parameters of this candidate
+    fn describe(&self) -> String {
+        format!(
+            "temperature {:.2} | cooling factor {:.5} | iterations {} | chances (move {}, \
+             unschedule {}, swap {})",
+            self.initial_temperature,
+            self.cooling_factor,
+            self.iterations,
+            self.move_chance,
+            self.unschedule_chance,
+            self.swap_chance
+        )
+    }
+}
+
+/// The outcome of a tuning
+///
+/// * `candidates` - The raced configurations. The first one holds the configured parameters
+/// * `recommended` - The index of the recommended configuration
+/// * `rounds` - The number of rounds completed by every remaining configuration
+/// * `stop_reason` - Why the race stopped before a single configuration was left, if it did
+pub struct TuningResult {
+    pub candidates: Vec<TuningCandidate>,
+    pub recommended: usize,
+    pub rounds: u32,
+    pub stop_reason: Option<StopReason>,
+}
+
+/// Races parameter configurations against each other on repeated seeded solves of the constraints
+///
+/// Every round solves the constraints once with each remaining configuration, with the same random
+/// seed for all of them, so the configurations are compared on the same starting schedules. From
+/// `min_rounds` on, the configurations whose mean penalty is more than `elimination_threshold`
+/// standard errors worse than the best mean are eliminated. The race ends when one configuration
+/// is left, `max_rounds` is reached, the time budget is used up or the user interrupts it. A round
+/// cut short by the time budget or the user is discarded, and a configuration whose solve fails is
+/// eliminated
+///
+/// NOTE: The configurations are compared on the scores of the solves, so the tuning respects the
+/// lexicographic scoring mode
+///
+/// # Arguments
+/// * `constraints` - The constraint store containing the constraints to schedule
+/// * `config` - The configuration of the solves and the tuning
+/// * `scoring` - The scoring the solves are compared with
+///
+/// # Returns
+/// * `TuningResult` - The penalties of every configuration and the recommended configuration
+/// * `ScoringError` - If the penalties scaled by the scoring do not fit in a u32
+pub fn run_tuning(
+    constraints: &ConstraintStore,
+    config: &Config,
+    scoring: &Scoring,
+) -> Result<TuningResult, ScoringError> {
+    let tuning_config = &config.tuning_config;
+    let search_config = scoring.search_config(config)?;
+    let deadline = tuning_config
+        .time_budget()
+        .and_then(|time_budget| Instant::now().checked_add(time_budget));
+
+    let mut candidates = vec![TuningCandidate::from_config(config)];
+    candidates.extend(
+        (1..tuning_config.candidates)
+            .map(|index| TuningCandidate::draw(tuning_config, index, config.random_seed)),
+    );
+    match tuning_config.time_budget() {
+        Some(time_budget) => info!(
+            "Tuning {} parameter configurations within {:?}",
+            candidates.len(),
+            time_budget
+        ),
+        None => info!("Tuning {} parameter configurations", candidates.len()),
+    }
+
+    let mut rounds = 0;
+    let mut stop_reason = None;
+    'race: for round in 0..tuning_config.max_rounds {
+        let random_seed = config.random_seed.wrapping_add(round);
+
+        // NOTE: The penalties of a round are only recorded once every remaining configuration
+        // has completed it, so the remaining configurations always have the same number of solves
+        let mut round_penalties: Vec<(usize, u32)> = Vec::new();
+        for (index, candidate) in candidates
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.eliminated_in_round.is_none())
+        {
+            let remaining_time = deadline.map_or(Duration::MAX, |deadline| {
+                deadline.saturating_duration_since(Instant::now())
+            });
+            if is_interrupted() {
+                stop_reason = Some(StopReason::Interrupted);
+                break 'race;
+            }
+            if remaining_time.is_zero() {
+                stop_reason = Some(StopReason::TimeLimit);
+                break 'race;
+            }
+
+            let configured_limit = search_config.time_limit_config.global_search_limit();
+            let is_cut_by_budget = configured_limit.is_none_or(|limit| remaining_time < limit);
+            let mut solve_config = candidate.apply(&search_config);
+            solve_config.random_seed = random_seed;
+            solve_config.time_limit_config.global_search_seconds = Some(
+                configured_limit
+                    .map_or(remaining_time, |limit| limit.min(remaining_time))
+                    .as_secs_f32(),
+            );
+
+            let result = run_global_search(constraints, &solve_config);
+            if is_interrupted() {
+                stop_reason = Some(StopReason::Interrupted);
+                break 'race;
+            }
+            if is_cut_by_budget && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                stop_reason = Some(StopReason::TimeLimit);
+                break 'race;
+            }
+
+            match result {
+                Ok((_, total_penalty, _)) => round_penalties.push((index, total_penalty)),
+                Err(error) => {
+                    warn!(
+                        "Solve of configuration {} failed, so it is eliminated: {}",
+                        index, error
+                    );
+                    candidate.eliminated_in_round = Some(round + 1);
+                }
+            }
+        }
+
+        for (index, penalty) in round_penalties {
+            candidates[index].penalties.push(penalty);
+        }
+        rounds = round + 1;
+
+        let remaining_candidates = match rounds >= tuning_config.min_rounds {
+            true => {
+                eliminate_candidates(&mut candidates, rounds, tuning_config.elimination_threshold)
+            }
+            false => candidates.len(),
+        };
+        info!(
+            "Round {} complete. {} configurations remaining",
+            rounds, remaining_candidates
+        );
+        if remaining_candidates <= 1 {
+            break;
+        }
+    }
+
+    if rounds == 0 {
+        warn!("No round of the tuning was completed, so the configured parameters are recommended");
+    }
+    let recommended = best_candidate(&candidates).unwrap_or(0);
+    Ok(TuningResult {
+        candidates,
+        recommended,
+        rounds,
+        stop_reason,
+    })
+}
+
+/// Returns the index of the remaining candidate with the lowest mean penalty, breaking ties on
+/// the lowest variance
+fn best_candidate(candidates: &[TuningCandidate]) -> Option<usize> {
+    candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| candidate.eliminated_in_round.is_none())
+        .min_by(|(_, first), (_, second)| {
+            first
+                .mean()
+                .total_cmp(&second.mean())
+                .then(first.variance().total_cmp(&second.variance()))
+        })
+        .map(|(index, _)| index)
+}
+
+/// Eliminates the candidates significantly worse than the best remaining candidate
+///
+/// A candidate is eliminated when the difference between its mean penalty and the best mean
+/// penalty is larger than `elimination_threshold` times the standard error of the difference.
+/// Only candidates with as many solves as the best candidate are compared
+///
+/// # Arguments
+/// * `candidates` - The candidates of the race
+/// * `round` - The round the candidates are eliminated in
+/// * `elimination_threshold` - The number of standard errors
+///
+/// # Returns
+/// * `usize` - The number of candidates remaining
+fn eliminate_candidates(
+    candidates: &mut [TuningCandidate],
+    round: u32,
+    elimination_threshold: f32,
+) -> usize {
+    let Some(best) = best_candidate(candidates) else {
+        return 0;
+    };
+    let sample_count = candidates[best].penalties.len();
+    if sample_count == 0 {
+        return candidates
+            .iter()
+            .filter(|candidate| candidate.eliminated_in_round.is_none())
+            .count();
+    }
+    let best_mean = candidates[best].mean();
+    let best_squared_error = candidates[best].variance() / sample_count as f64;
+
+    for candidate in candidates.iter_mut().filter(|candidate| {
+        candidate.eliminated_in_round.is_none() && candidate.penalties.len() == sample_count
+    }) {
+        let difference = candidate.mean() - best_mean;
+        let standard_error =
+            (candidate.variance() / candidate.penalties.len() as f64 + best_squared_error).sqrt();
+        if difference > 0.0 && difference > elimination_threshold as f64 * standard_error {
+            candidate.eliminated_in_round = Some(round);
+        }
+    }
+
+    candidates
+        .iter()
+        .filter(|candidate| candidate.eliminated_in_round.is_none())
+        .count()
+}
+
+impl TuningResult {
+    /// Writes the penalties of every raced configuration to the report output, best first
+    pub fn print_report(&self) {
+        report!("=== TUNING REPORT ===");
+        report!("ROUNDS: {}", self.rounds);
+        if let Some(stop_reason) = self.stop_reason {
+            report!("STOPPED EARLY: {}", stop_reason);
+        }
+
+        for (index, candidate) in self.ranked_candidates() {
+            report!("▶ Configuration {}: {}", index, candidate.describe());
+            report!(
+                "  └─ Solves: {} | Mean penalty: {:.2} | Variance: {:.2}",
+                candidate.penalties.len(),
+                candidate.mean(),
+                candidate.variance()
+            );
+            match candidate.eliminated_in_round {
+                _ if index == self.recommended => report!("  └─ Recommended"),
+                Some(round) => report!("  └─ Eliminated in round {}", round),
+                None => {}
+            }
+        }
+        report!("=====================");
+    }
+
+    /// Returns the candidates with their indices, ordered by mean penalty
+    fn ranked_candidates(&self) -> Vec<(usize, &TuningCandidate)> {
+        let mut ranked: Vec<(usize, &TuningCandidate)> =
+            self.candidates.iter().enumerate().collect();
+        ranked.sort_by(|(_, first), (_, second)| first.mean().total_cmp(&second.mean()));
+        ranked
+    }
+
+    /// Writes the configuration with the recommended parameters to the tuning output path
+    ///
+    /// The penalties of every raced configuration are written as comments at the top of the file
+    ///
+    /// # Arguments
+    /// * `config` - The configuration to take the values other than the tuned parameters from
+    pub fn export_recommended_config(&self, config: &Config) -> Result<(), Box<dyn Error>> {
+        let output_path = &config.tuning_config.output_path;
+        info!("Exporting recommended configuration ({:?})", output_path);
+
+        let mut recommended_config = self.candidates[self.recommended].apply(config);
+        // NOTE: The time budget of the tuning shortens the time limits of the solves, the
+        // recommended configuration keeps the configured limits
+        recommended_config.time_limit_config = config.time_limit_config.clone();
+
+        let mut contents = format!(
+            "# Recommended by `scheduler tune` on {} after {} rounds\n#\n",
+            config.constraint_file_path, self.rounds
+        );
+        for (index, candidate) in self.ranked_candidates() {
+            contents += &format!(
+                "# Configuration {}{}: {}\n#   solves {} | mean penalty {:.2} | variance {:.2}\n",
+                index,
+                if index == self.recommended {
+                    " (recommended)"
+                } else {
+                    ""
+                },
+                candidate.describe(),
+                candidate.penalties.len(),
+                candidate.mean(),
+                candidate.variance()
+            );
+        }
+        contents += "\n";
+        contents += &toml::to_string_pretty(&recommended_config)?;
+
+        std::fs::write(output_path, contents)?;
+        info!("Exported recommended configuration");
+        Ok(())
+    }
+}